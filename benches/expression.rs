@@ -0,0 +1,58 @@
+// Evaluation of the expressions the interpreter runs most: numbers, variables, arithmetic, conditions and colours.
+// cargo bench --bench expression
+use std::collections::HashMap;
+use criterion::{ criterion_group, criterion_main, Criterion };
+use logo::expression::{ seed_random, Expression };
+use logo::parser::read_expression;
+
+fn expression(code: &str) -> Expression
+{
+    let symbols = read_expression(&mut format!("{code} ").chars().peekable());
+    Expression::new(symbols.iter().map(|symbol| symbol.as_str()).collect())
+}
+
+const NUMBERS: [(&str, &str); 6] = [
+    ("constant", "30"),
+    ("variable", ":size"),
+    ("global", ":who"),
+    ("arithmetic", ":size * 0.5 + :sign * -1"),
+    ("brackets", "( :size - 1 ) * ( :angle + 7 ) / ( 2 + :sign )"),
+    ("condition", ":size < 1")
+];
+
+const COLORS: [(&str, &str); 3] = [
+    ("name", "\"red"),
+    ("number", ":size / 5"),
+    ("pick", "pick [ red orange yellow green blue violet ]")
+];
+
+fn evaluate(c: &mut Criterion)
+{
+    seed_random(1);
+    let variables = HashMap::from([("size".to_string(), 25.0), ("sign".to_string(), -1.0), ("angle".to_string(), 45.0)]);
+    let globals = HashMap::from([("who".to_string(), 1.0), ("turtles".to_string(), 1.0)]);
+
+    let mut group = c.benchmark_group("evaluate");
+    for (name, code) in NUMBERS
+    {
+        let expression = expression(code);
+        group.bench_function(name, |b| b.iter(|| expression.evaluate(&variables, &globals).unwrap()));
+    }
+    let random = expression("random 100");
+    group.bench_function("random", |b| b.iter(|| random.evaluate(&variables, &globals).unwrap()));
+    group.finish();
+
+    let mut group = c.benchmark_group("evaluate_color");
+    for (name, code) in COLORS
+    {
+        let expression = expression(code);
+        group.bench_function(name, |b| b.iter(|| expression.evaluate_color(&variables, &globals).unwrap()));
+    }
+    group.finish();
+
+    // The parser builds one for every parameter it reads
+    c.bench_function("expression_new", |b| b.iter(|| expression(NUMBERS[4].1)));
+}
+
+criterion_group!(benches, evaluate);
+criterion_main!(benches);
@@ -0,0 +1,25 @@
+// Compares the tree-walker with the bytecode virtual machine on the heaviest programs of resources/.
+// cargo bench --bench interpreter, parsing is left out of the measure
+use std::fs;
+use criterion::{ criterion_group, criterion_main, BatchSize, Criterion };
+use logo::interpreter::{ execute_logo_program, walk_logo_program, InterpreterOptions };
+use logo::logo_manager::prepare_code;
+use logo::parser::parse_logo_code;
+
+const PROGRAMS: [&str; 4] = ["sun", "fern", "tree", "rotating_circle"];
+
+fn engines(c: &mut Criterion)
+{
+    let options = InterpreterOptions{ seed: Some(1), ..Default::default() };
+    for program in PROGRAMS
+    {
+        let code = prepare_code(&fs::read_to_string(format!("resources/{program}.logo")).unwrap());
+        let mut group = c.benchmark_group(program);
+        group.bench_function("tree-walker", |b| b.iter_batched(|| parse_logo_code(&code), |procedures| walk_logo_program(procedures, &options).unwrap(), BatchSize::SmallInput));
+        group.bench_function("vm", |b| b.iter_batched(|| parse_logo_code(&code), |procedures| execute_logo_program(procedures, &options).unwrap(), BatchSize::SmallInput));
+        group.finish();
+    }
+}
+
+criterion_group!(benches, engines);
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+// Parsing of generated programs of growing size, reported per byte of source.
+// cargo bench --bench parser
+use criterion::{ criterion_group, criterion_main, BenchmarkId, Criterion, Throughput };
+use logo::logo_manager::prepare_code;
+use logo::parser::parse_logo_code;
+
+// Procedures using every kind of block and call, followed by a main program calling each of them
+fn generated_source(procedures: usize) -> String
+{
+    let mut code = String::new();
+    for procedure in 0..procedures
+    {
+        code.push_str(&format!("to shape{procedure} :size :angle\n"));
+        code.push_str("    if :size < 2 [ stop ]\n");
+        code.push_str("    repeat 4 [ fd :size * 2 + ( :angle - repcount ) / 3 rt 90 ]\n");
+        code.push_str("    tell [ 3 ] [ setcolor pick [ red orange 3 ] fd :size lt :angle * 0.5 bk :size ]\n");
+        code.push_str("    ask [ 1 2 ] [ setcolor random 16 fd :size / 2 ]\n");
+        if procedure > 0
+        {
+            code.push_str(&format!("    shape{} :size - 1 :angle + 7\n", procedure - 1));
+        }
+        code.push_str("end\n");
+    }
+    for procedure in 0..procedures
+    {
+        code.push_str(&format!("pu bk 10 pd shape{procedure} {} 45\n", procedure % 50));
+    }
+    prepare_code(&code)
+}
+
+fn parse(c: &mut Criterion)
+{
+    let mut group = c.benchmark_group("parse_logo_code");
+    for procedures in [10, 100, 1000]
+    {
+        let code = generated_source(procedures);
+        group.throughput(Throughput::Bytes(code.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(procedures), &code, |b, code| b.iter(|| parse_logo_code(code)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
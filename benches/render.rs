@@ -0,0 +1,37 @@
+// Whole runs from source to output file contents on the heaviest programs of resources/, as the command line does them.
+// cargo bench --bench render
+use std::fs;
+use criterion::{ criterion_group, criterion_main, Criterion };
+use logo::drawing::Drawing;
+use logo::interpreter::{ execute_logo_program, InterpreterOptions };
+use logo::logo_manager::prepare_code;
+use logo::parser::parse_logo_code;
+use logo::renderer::svg::SvgRenderer;
+use logo::renderer::Renderer;
+
+const PROGRAMS: [&str; 4] = ["fern", "rotating_circle", "sun", "tree"];
+
+fn run(code: &str) -> Drawing
+{
+    execute_logo_program(parse_logo_code(&prepare_code(code)), &InterpreterOptions{ seed: Some(1), ..Default::default() }).unwrap()
+}
+
+fn render(c: &mut Criterion)
+{
+    for program in PROGRAMS
+    {
+        let code = fs::read_to_string(format!("resources/{program}.logo")).unwrap();
+        let mut group = c.benchmark_group(program);
+        group.bench_function("svg", |b| b.iter(|| SvgRenderer::default().render(&run(&code))));
+        #[cfg(feature = "png")]
+        {
+            let renderer = logo::renderer::png::PngRenderer::default();
+            group.sample_size(10);
+            group.bench_function("png", |b| b.iter(|| renderer.render(&run(&code))));
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, render);
+criterion_main!(benches);
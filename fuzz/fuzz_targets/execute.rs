@@ -0,0 +1,18 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use logo::interpreter::{ execute_logo_program, InterpreterOptions };
+use logo::logo_manager::prepare_code;
+use logo::parser::parse_logo_code;
+
+// Small enough for endless loops to stop in a few milliseconds
+const MAX_INSTRUCTIONS: u64 = 5000;
+const MAX_PRIMITIVES: usize = 1000;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(code) = std::str::from_utf8(data)
+    {
+        let options = InterpreterOptions { seed: Some(1), max_instructions: Some(MAX_INSTRUCTIONS), max_primitives: Some(MAX_PRIMITIVES), ..Default::default() };
+        let _ = execute_logo_program(parse_logo_code(&prepare_code(code)), &options);
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use logo::logo_manager::prepare_code;
+use logo::parser::parse_logo_code;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(code) = std::str::from_utf8(data)
+    {
+        parse_logo_code(&prepare_code(code));
+    }
+});
@@ -0,0 +1,210 @@
+use std::path::Path;
+use std::time::Duration;
+
+use crate::logo_manager::Options;
+use crate::renderer::shape_outlines;
+use crate::renderer::vector::PageSetup;
+use crate::renderer::preview::PreviewRenderer;
+use crate::server::DEFAULT_PORT;
+
+pub const USAGE: &str = "Usage: logo [options] [<input.logo> [-o <output.svg>]]...
+       logo repl [options]
+       logo serve [<directory>] [--port <port>] [options]
+
+Renders each input Logo program to an SVG file, by default next to the input with the .svg extension.
+The format follows the extension of the output file: .svg, .pdf, .eps, .html for a page replaying the drawing,
+.json or .jsonl (JSON Lines) for a log of every turtle action, .gcode (.nc, .ngc) or .hpgl (.plt) for pen plotters,
+or .png and an animated .gif when built with the png and gif features.
+Without inputs the example programs from resources/ are rendered to svg/.
+logo repl reads instructions interactively, keeping the turtles and procedures between them.
+logo serve shows the drawings of the programs in the directory (default the current one) at http://localhost:<port>/
+and reloads them as the programs change, stopping programs after 10 seconds or 10000000 instructions by default.
+
+Options:
+    -o, --output <file>    output file of the preceding input
+    --width <pixels>       canvas width, the turtles start in its centre (default 1100)
+    --height <pixels>      canvas height (default 600)
+    --seed <number>        seed of random and pick, for the same drawing on every run
+    --max-steps <count>    stop a program with an error after running this many instructions
+    --max-depth <calls>    stop a program with an error when procedure calls nest deeper (default 10000)
+    --max-output <count>   stop a program with an error after drawing this many lines, shapes and labels
+    --max-turtles <count>  highest turtle number setturtle, tell and ask accept (default 100)
+    --timeout <seconds>    stop a program with an error when it runs longer
+    --shape <name>=<file>  add a turtle shape for setshape, SVG polygon, circle, ellipse and rect elements
+                           drawn around (0,0) and pointing up
+    --fit                  fit the image to the drawing instead of the whole canvas
+    --margin <pixels>      space left around the drawing with --fit (default 10)
+    --precision <digits>   round coordinates to this many decimal places (default 2)
+    --no-merge             write every line separately instead of joining connected ones
+    --animate              draw the SVG over time, following the waits of the program
+    --watch                render again whenever an input changes, keeping the last good output on errors
+    --port <port>          port of logo serve (default 8080)
+    --page <a4|letter>     paper size of PDF and EPS output (default a4)
+    --landscape            turn the page sideways
+    --scale-to-fit         fill the page, otherwise 96 pixels per inch or shrunk when too big
+    --preview              print the drawings to the terminal instead of saving them
+    --braille              preview with braille dots, finer but with one colour per character
+    --columns <count>      width of the preview in characters (default $COLUMNS or 80)
+    --mm-per-pixel <mm>    size on paper of a canvas pixel when plotting (default 0.25)
+    --feed-rate <mm/min>   G-code drawing speed (default 1500)
+    --pen-up <commands>    G-code lifting the pen (default \"G0 Z5\")
+    --pen-down <commands>  G-code lowering the pen (default \"G1 Z0\")
+    --keep-order           plot in drawing order instead of shortening the pen-up travel
+    --dpi <dots>           resolution of PNG images, 96 is a pixel per canvas pixel (default 96)
+    -h, --help             print this message";
+
+pub enum Command
+{
+    RENDER(Box<Arguments>),
+    REPL(Box<Options>),
+    SERVE(Box<ServerArguments>),
+    HELP
+}
+
+pub struct Arguments
+{
+    pub options: Options,
+    pub logo_file_paths: Vec<String>,
+    pub output_file_paths: Vec<String>,
+    pub watch: bool  // render again whenever an input changes
+}
+
+pub struct ServerArguments
+{
+    pub options: Options,
+    pub directory: String,
+    pub port: u16
+}
+
+fn default_output_path(logo_file_path: &str) -> String
+{
+    Path::new(logo_file_path).with_extension("svg").to_string_lossy().to_string()
+}
+
+fn read_value<T: std::str::FromStr>(option: &str, value: Option<&String>) -> Result<T, String>
+{
+    let value = value.ok_or(format!("Missing value of {option}"))?;
+    value.parse::<T>().map_err(|_| format!("Invalid value of {option}: {value}"))
+}
+
+pub fn parse_arguments(arguments: &[String]) -> Result<Command, String>
+{
+    let mut options = Options::default();
+    let mut logo_file_paths: Vec<String> = vec!();
+    let mut output_file_paths: Vec<String> = vec!();
+    let mut fit_to_content = false;
+    let mut margin = options.svg.margin;
+    let mut page = PageSetup::default();
+    let (mut preview, mut braille) = (false, false);
+    let mut preview_width: Option<usize> = None;
+    let mut watch = false;
+
+    let subcommand = arguments.first().map(|argument| argument.as_str()).filter(|argument| ["repl", "serve"].contains(argument));
+    let mut port = DEFAULT_PORT;
+    let mut arguments_iterator = arguments.iter().skip(subcommand.is_some() as usize);
+    while let Some(argument) = arguments_iterator.next()
+    {
+        match argument.as_str()
+        {
+            "-h" | "--help" => return Ok(Command::HELP),
+            "-o" | "--output" =>
+            {
+                let output_file_path: String = read_value(argument, arguments_iterator.next())?;
+                match output_file_paths.last_mut()
+                {
+                    Some(last_output_file_path) => *last_output_file_path = output_file_path,
+                    None => return Err(format!("{argument} has to follow an input file"))
+                }
+            }
+            "--width" => options.interpreter.canvas_width = read_value(argument, arguments_iterator.next())?,
+            "--height" => options.interpreter.canvas_height = read_value(argument, arguments_iterator.next())?,
+            "--seed" => options.interpreter.seed = Some(read_value(argument, arguments_iterator.next())?),
+            "--max-steps" => options.interpreter.max_instructions = Some(read_value(argument, arguments_iterator.next())?),
+            "--max-depth" => options.interpreter.max_depth = Some(read_value(argument, arguments_iterator.next())?),
+            "--max-output" => options.interpreter.max_primitives = Some(read_value(argument, arguments_iterator.next())?),
+            "--max-turtles" => options.interpreter.max_turtles = read_value(argument, arguments_iterator.next())?,
+            "--timeout" =>
+            {
+                let seconds: f64 = read_value(argument, arguments_iterator.next())?;
+                options.interpreter.timeout = Some(Duration::try_from_secs_f64(seconds).map_err(|_| format!("Invalid value of {argument}: {seconds}"))?);
+            }
+            "--shape" =>
+            {
+                let value: String = read_value(argument, arguments_iterator.next())?;
+                let (name, shape_file_path) = value.split_once('=').filter(|(name, _)| !name.is_empty()).ok_or(format!("Invalid value of {argument}: {value}"))?;
+                let markup = std::fs::read_to_string(shape_file_path).map_err(|error| format!("Cannot read {shape_file_path}: {error}"))?;
+                shape_outlines(&markup).map_err(|error| format!("Invalid turtle shape in {shape_file_path}: {error}"))?;
+                options.interpreter.shapes.insert(name.to_string(), markup.trim().to_string());
+            }
+            "--fit" => fit_to_content = true,
+            "--margin" => margin = read_value(argument, arguments_iterator.next())?,
+            "--precision" => options.svg.precision = Some(read_value(argument, arguments_iterator.next())?),
+            "--no-merge" => options.svg.merge_segments = false,
+            "--animate" => options.svg.animate = true,
+            "--watch" => watch = true,
+            "--port" => port = read_value(argument, arguments_iterator.next())?,
+            "--page" => page.size = read_value(argument, arguments_iterator.next())?,
+            "--landscape" => page.landscape = true,
+            "--scale-to-fit" => page.scale_to_fit = true,
+            "--preview" => preview = true,
+            "--braille" => (preview, braille) = (true, true),
+            "--columns" => preview_width = Some(read_value(argument, arguments_iterator.next())?),
+            "--mm-per-pixel" => options.set_plot_scale(read_value(argument, arguments_iterator.next())?),
+            "--feed-rate" => options.gcode.feed_rate = read_value(argument, arguments_iterator.next())?,
+            "--pen-up" => options.gcode.pen_up = read_value(argument, arguments_iterator.next())?,
+            "--pen-down" => options.gcode.pen_down = read_value(argument, arguments_iterator.next())?,
+            "--keep-order" => options.set_travel_optimization(false),
+            #[cfg(feature = "png")]
+            "--dpi" => options.png.dpi = read_value(argument, arguments_iterator.next())?,
+            _ if argument.starts_with('-') => return Err(format!("Unknown option: {argument}")),
+            _ =>
+            {
+                logo_file_paths.push(argument.clone());
+                output_file_paths.push(default_output_path(argument));
+            }
+        }
+    }
+
+    if preview
+    {
+        let columns = preview_width.or_else(|| std::env::var("COLUMNS").ok().and_then(|columns| columns.parse().ok())).unwrap_or(80);
+        options.preview = Some(PreviewRenderer { columns, braille, ..PreviewRenderer::default() });
+    }
+    if fit_to_content
+    {
+        options.fit_to_content(margin);
+    }
+    options.set_page_setup(page);
+
+    if options.interpreter.canvas_width <= 0 || options.interpreter.canvas_height <= 0
+    {
+        return Err("The canvas size has to be positive".to_string());
+    }
+    if options.interpreter.max_turtles == 0
+    {
+        return Err("There has to be at least one turtle".to_string());
+    }
+    #[cfg(feature = "png")]
+    if options.png.dpi <= 0.0
+    {
+        return Err("The resolution has to be positive".to_string());
+    }
+    if options.gcode.millimetres_per_unit <= 0.0 || options.gcode.feed_rate <= 0.0
+    {
+        return Err("The plotting scale and feed rate have to be positive".to_string());
+    }
+
+    match subcommand
+    {
+        Some("repl") if !logo_file_paths.is_empty() => Err("The REPL takes no input files".to_string()),
+        Some("repl") => Ok(Command::REPL(Box::new(options))),
+        Some("serve") if logo_file_paths.len() > 1 => Err("logo serve takes a single directory".to_string()),
+        Some("serve") =>
+        {
+            let directory = logo_file_paths.pop().unwrap_or(".".to_string());
+            Ok(Command::SERVE(Box::new(ServerArguments { options, directory, port })))
+        }
+        _ if watch && logo_file_paths.is_empty() => Err("--watch needs input files".to_string()),
+        _ => Ok(Command::RENDER(Box::new(Arguments { options, logo_file_paths, output_file_paths, watch })))
+    }
+}
@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+use lazy_static::lazy_static;
+
+pub const DEFAULT_BACKGROUND_COLOR: &str = "rgb(255,255,255)";
+pub const DEFAULT_TURTLE_SHAPE: &str = "triangle";
+pub const SPRITE_RADIUS: f32 = 15.0; // every turtle shape fits in a circle of this radius
+pub const TICKS_PER_SECOND: f32 = 60.0; // wait counts in sixtieths of a second
+
+lazy_static!
+{
+    // SVG markup of the shapes available in setshape, drawn around (0,0) and pointing up
+    pub static ref TURTLE_SHAPES: HashMap<&'static str, &'static str> =
+    {
+        let mut map = HashMap::new();
+        map.insert("triangle", "<polygon points=\"0,-15 8,5 -8,5\" />");
+        map.insert("arrow", "<polygon points=\"0,-15 10,-3 4,-3 4,10 -4,10 -4,-3 -10,-3\" />");
+        map.insert("circle", "<circle r=\"8\" />");
+        map.insert("square", "<rect x=\"-8\" y=\"-8\" width=\"16\" height=\"16\" />");
+        map.insert("turtle", "<ellipse rx=\"7\" ry=\"9\" /><circle cy=\"-12\" r=\"3.5\" /><circle cx=\"-7\" cy=\"-6\" r=\"2.5\" /><circle cx=\"7\" cy=\"-6\" r=\"2.5\" /><circle cx=\"-7\" cy=\"6\" r=\"2.5\" /><circle cx=\"7\" cy=\"6\" r=\"2.5\" />");
+        map
+    };
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PenMode
+{
+    PAINT,   // draws with the pen colour
+    ERASE,   // draws with the background colour
+    REVERSE  // inverts what is underneath, drawing the same line twice restores it
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Style
+{
+    pub stroke_color: String,
+    pub stroke_width: f32,
+    pub fill_color: Option<String>,
+    pub pen_mode: PenMode
+}
+
+#[derive(Clone)]
+pub struct Segment
+{
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+    pub style: Style
+}
+
+#[derive(Clone)]
+pub struct Polygon
+{
+    pub points: Vec<(f32, f32)>,
+    pub style: Style
+}
+
+// Angles are in degrees, measured clockwise from the x axis as the canvas y axis points down
+#[derive(Clone)]
+pub struct Arc
+{
+    pub center_x: f32,
+    pub center_y: f32,
+    pub radius: f32,
+    pub start_angle: f32,
+    pub sweep_angle: f32,
+    pub style: Style
+}
+impl Arc
+{
+    pub fn point_at(&self, angle: f32) -> (f32, f32)
+    {
+        let angle_rad = angle.to_radians();
+        (self.center_x + self.radius * angle_rad.cos(), self.center_y + self.radius * angle_rad.sin())
+    }
+}
+
+#[derive(Clone)]
+pub struct Text
+{
+    pub x: f32,
+    pub y: f32,
+    pub color: String,
+    pub font_size: i32,
+    pub rotation_angle: f32,
+    pub text: String
+}
+
+// Turtle drawn on top of the picture, rotation_angle is measured from pointing up
+#[derive(Clone)]
+pub struct Sprite
+{
+    pub x: f32,
+    pub y: f32,
+    pub color: String,
+    pub rotation_angle: f32,
+    pub shape: String  // SVG markup, drawn around (0,0) and pointing up
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BoundingBox
+{
+    pub min_x: f32,
+    pub min_y: f32,
+    pub max_x: f32,
+    pub max_y: f32
+}
+impl BoundingBox
+{
+    pub fn from_point(x: f32, y: f32) -> Self
+    {
+        Self { min_x: x, min_y: y, max_x: x, max_y: y }
+    }
+
+    pub fn include_point(&mut self, x: f32, y: f32)
+    {
+        self.min_x = self.min_x.min(x);
+        self.min_y = self.min_y.min(y);
+        self.max_x = self.max_x.max(x);
+        self.max_y = self.max_y.max(y);
+    }
+
+    pub fn include(&mut self, other: &BoundingBox)
+    {
+        self.include_point(other.min_x, other.min_y);
+        self.include_point(other.max_x, other.max_y);
+    }
+
+    pub fn expand(&mut self, margin: f32)
+    {
+        self.min_x -= margin;
+        self.min_y -= margin;
+        self.max_x += margin;
+        self.max_y += margin;
+    }
+
+    pub fn width(&self) -> f32
+    {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> f32
+    {
+        self.max_y - self.min_y
+    }
+}
+
+// What a turtle did, kept in the order of the program for the outputs replaying it
+#[derive(Clone)]
+pub enum ActionKind
+{
+    MOVE,                                             // from one point to another, drawing when the pen is down
+    TURN,
+    LABEL { text: String, font_size: i32 },
+    WAIT { ticks: u32 },
+    CLEAR,                                            // everything drawn so far is erased
+    HOME,                                             // back to the centre facing up without drawing, after clearscreen
+    SET_BACKGROUND { color: String },
+    SHOW,
+    HIDE
+}
+
+// The action along with the state of the turtle when it did it
+#[derive(Clone)]
+pub struct TurtleAction
+{
+    pub kind: ActionKind,
+    pub turtle: usize,
+    pub tick: u32,           // time of the action, in ticks of waiting since the start
+    pub from: (f32, f32),
+    pub to: (f32, f32),
+    pub heading: f32,        // degrees clockwise from up, as in setheading
+    pub pen_down: bool,
+    pub pen_mode: PenMode,
+    pub color: String,
+    pub width: f32
+}
+
+#[derive(Clone)]
+pub enum DrawingElement
+{
+    SEGMENT(Segment),
+    POLYGON(Polygon),
+    ARC(Arc),
+    TEXT(Text)
+}
+
+impl DrawingElement
+{
+    pub fn bounding_box(&self) -> BoundingBox
+    {
+        match self
+        {
+            DrawingElement::SEGMENT(segment) =>
+            {
+                let mut bounding_box = BoundingBox::from_point(segment.x1, segment.y1);
+                bounding_box.include_point(segment.x2, segment.y2);
+                bounding_box.expand(segment.style.stroke_width / 2.0);
+                bounding_box
+            }
+            DrawingElement::POLYGON(polygon) =>
+            {
+                let mut bounding_box = BoundingBox::from_point(polygon.points[0].0, polygon.points[0].1);
+                for (x, y) in polygon.points.iter()
+                {
+                    bounding_box.include_point(*x, *y);
+                }
+                bounding_box.expand(polygon.style.stroke_width / 2.0);
+                bounding_box
+            }
+            DrawingElement::ARC(arc) =>
+            {
+                let end_angle = arc.start_angle + arc.sweep_angle;
+                let (start_x, start_y) = arc.point_at(arc.start_angle);
+                let mut bounding_box = BoundingBox::from_point(start_x, start_y);
+                let (end_x, end_y) = arc.point_at(end_angle);
+                bounding_box.include_point(end_x, end_y);
+                // Extreme points of the circle lie at multiples of 90 degrees
+                let mut angle = (arc.start_angle.min(end_angle) / 90.0).ceil() * 90.0;
+                while angle <= arc.start_angle.max(end_angle).min(arc.start_angle.min(end_angle) + 360.0)
+                {
+                    let (x, y) = arc.point_at(angle);
+                    bounding_box.include_point(x, y);
+                    angle += 90.0;
+                }
+                bounding_box.expand(arc.style.stroke_width / 2.0);
+                bounding_box
+            }
+            DrawingElement::TEXT(text) =>
+            {
+                // Estimated from the font size, as the real glyph metrics depend on the viewer
+                let text_width = text.font_size as f32 * 0.6 * text.text.chars().count() as f32;
+                let text_height = text.font_size as f32;
+                let (sin, cos) = text.rotation_angle.to_radians().sin_cos();
+                let mut bounding_box = BoundingBox::from_point(text.x, text.y);
+                for (dx, dy) in [(text_width, 0.0), (0.0, -text_height), (text_width, -text_height)]
+                {
+                    bounding_box.include_point(text.x + dx * cos - dy * sin, text.y + dx * sin + dy * cos);
+                }
+                bounding_box
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Drawing
+{
+    pub width: i32,
+    pub height: i32,
+    pub background_color: String,
+    elements: Vec<DrawingElement>,
+    element_ticks: Vec<u32>,  // time at which each element was drawn, in ticks of waiting since the start
+    sprites: Vec<Sprite>,
+    actions: Vec<TurtleAction>,
+    bounding_box: Option<BoundingBox>,
+    current_tick: u32
+}
+impl Drawing
+{
+    pub fn new(width: i32, height: i32) -> Self
+    {
+        Self { width, height, background_color: DEFAULT_BACKGROUND_COLOR.to_string(), elements: vec!(), element_ticks: vec!(), sprites: vec!(), actions: vec!(), bounding_box: None, current_tick: 0 }
+    }
+
+    pub fn add_element(&mut self, element: DrawingElement)
+    {
+        self.include_in_bounding_box(&element.bounding_box());
+        self.elements.push(element);
+        self.element_ticks.push(self.current_tick);
+    }
+
+    pub fn get_elements(&self) -> &Vec<DrawingElement>
+    {
+        &self.elements
+    }
+
+    pub fn get_element_ticks(&self) -> &Vec<u32>
+    {
+        &self.element_ticks
+    }
+
+    // The time stops at the largest tick rather than overflowing
+    pub fn wait(&mut self, ticks: u32)
+    {
+        self.current_tick = self.current_tick.saturating_add(ticks);
+    }
+
+    // Ticks waited in total, the time the animation of the drawing takes
+    pub fn get_duration(&self) -> u32
+    {
+        self.current_tick
+    }
+
+    pub fn add_action(&mut self, mut action: TurtleAction)
+    {
+        action.tick = self.current_tick;
+        self.actions.push(action);
+    }
+
+    pub fn get_actions(&self) -> &Vec<TurtleAction>
+    {
+        &self.actions
+    }
+
+    pub fn add_sprite(&mut self, sprite: Sprite)
+    {
+        let mut sprite_box = BoundingBox::from_point(sprite.x, sprite.y);
+        sprite_box.expand(SPRITE_RADIUS);
+        self.include_in_bounding_box(&sprite_box);
+        self.sprites.push(sprite);
+    }
+
+    pub fn get_sprites(&self) -> &Vec<Sprite>
+    {
+        &self.sprites
+    }
+
+    // Box around everything drawn and the sprites, None for an empty drawing
+    pub fn get_bounding_box(&self) -> Option<BoundingBox>
+    {
+        self.bounding_box
+    }
+
+    fn include_in_bounding_box(&mut self, element_box: &BoundingBox)
+    {
+        match &mut self.bounding_box
+        {
+            Some(bounding_box) => bounding_box.include(element_box),
+            None => self.bounding_box = Some(*element_box)
+        }
+    }
+
+    // Erases everything drawn so far, the background colour is kept
+    pub fn clear(&mut self)
+    {
+        self.elements.clear();
+        self.element_ticks.clear();
+        self.bounding_box = None;
+    }
+}
@@ -1,5 +1,8 @@
+use std::cell::RefCell;
 use std::collections::{ LinkedList, HashMap, HashSet };
-use rand::Rng;
+use std::fmt;
+use rand::rngs::StdRng;
+use rand::{ Rng, SeedableRng };
 use lazy_static::lazy_static;
 
 lazy_static! 
@@ -24,11 +27,77 @@ lazy_static!
     };
 }
 
-#[derive(Clone)]
-enum ExpressionSymbol
+thread_local!
+{
+    // Source of random and pick, seeded for drawings that come out the same every time
+    static RANDOM: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+pub fn seed_random(seed: u64)
+{
+    RANDOM.with(|random| *random.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+// Whole number from 0 up to the bound, which is rounded towards 0
+pub fn random_number(high_bound: f32) -> Result<f32, ExpressionError>
+{
+    if (high_bound as i32) < 1
+    {
+        return Err(ExpressionError::INVALID_RANDOM_BOUND(high_bound));
+    }
+    let picked_number = RANDOM.with(|random| random.borrow_mut().gen_range(0..high_bound as i32));
+    Ok(picked_number as f32)
+}
+
+// Index of the entry picked among the given number of them
+pub fn random_index(count: usize) -> usize
+{
+    RANDOM.with(|random| random.borrow_mut().gen_range(0..count))
+}
+
+// Standard Logo colours available by number in setcolor and setbackground
+const PALETTE: [&str; 16] = ["black", "blue", "lime", "cyan", "red", "magenta", "yellow", "white",
+                             "brown", "tan", "green", "aquamarine", "salmon", "purple", "orange", "gray"];
+
+pub fn palette_color(number: f32) -> String
+{
+    PALETTE[number.round() as usize % PALETTE.len()].to_string()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExpressionError
+{
+    EMPTY,
+    INVALID_NUMBER(String),
+    UNKNOWN_VARIABLE(String),
+    MISSING_OPERAND(String),
+    INVALID_RANDOM_BOUND(f32),
+    EMPTY_PICK,
+    INVALID_COLOR
+}
+impl fmt::Display for ExpressionError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            ExpressionError::EMPTY => write!(f, "missing value"),
+            ExpressionError::INVALID_NUMBER(number) => write!(f, "{number} is not a number"),
+            ExpressionError::UNKNOWN_VARIABLE(variable) => write!(f, "unknown variable: {variable}"),
+            ExpressionError::MISSING_OPERAND(operator) => write!(f, "missing value around {operator}"),
+            ExpressionError::INVALID_RANDOM_BOUND(high_bound) => write!(f, "random needs a bound of at least 1, not {high_bound}"),
+            ExpressionError::EMPTY_PICK => write!(f, "pick needs at least one colour"),
+            ExpressionError::INVALID_COLOR => write!(f, "pick can only choose between colour names and numbers")
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExpressionSymbol
 {
     VARIABLE(String),
     CONSTANT(f32),
+    INVALID_NUMBER(String),  // made of digits, dots and minuses but not a number, such as 4-2
     OPERATOR(String),
     BRACKET_OPENING,
     BRACKET_CLOSING,
@@ -44,11 +113,11 @@ impl Expression
 {
     pub fn new(expression_str: Vec<&str>) -> Self
     {
-        if expression_str.len() > 0
+        if !expression_str.is_empty()
         {
-            let is_function_command = FUNCTION_COMMANDS.contains(expression_str.first().unwrap());
+            let is_pick = *expression_str.first().unwrap() == "pick";
             let infix_symbol_list = Expression::create_list_of_expression_symbols(expression_str);
-            if !is_function_command
+            if !is_pick
             {
                 let postifx_symbol_list = Expression::infix_to_postfix(infix_symbol_list);
                 Self { postifx_symbol_list }
@@ -64,35 +133,33 @@ impl Expression
         }
     }
 
-    pub fn evaluate_setcolor(&self) -> String
-    {
-        let high_bound = self.postifx_symbol_list.len() - 1;
-        let picked_idx = rand::thread_rng().gen_range(0..high_bound);
-        if let ExpressionSymbol::VARIABLE(color) = self.postifx_symbol_list.iter().nth(picked_idx + 1).unwrap()
-        {
-            return color.clone()
-        }
-        panic!();
-    }
-
-    pub fn evaluate(&self, variables: &HashMap<String, f32>) -> f32
+    pub fn evaluate_color(&self, variables: &HashMap<String, f32>, globals: &HashMap<String, f32>) -> Result<String, ExpressionError>
     {
-        if let ExpressionSymbol::FUNCTION_COMMAND(function_command) = self.postifx_symbol_list.front().unwrap()
+        match self.postifx_symbol_list.front().ok_or(ExpressionError::EMPTY)?
         {
-            match function_command.as_str()
+            ExpressionSymbol::FUNCTION_COMMAND(function_command) if function_command == "pick" =>
             {
-                "random" =>
+                let high_bound = self.postifx_symbol_list.len() - 1;
+                if high_bound == 0
                 {
-                    if let ExpressionSymbol::CONSTANT(high_bound) = self.postifx_symbol_list.iter().nth(1).unwrap()
-                    {
-                        let picked_number = rand::thread_rng().gen_range(0..*high_bound as i32);
-                        return picked_number as f32;
-                    }
+                    return Err(ExpressionError::EMPTY_PICK);
+                }
+                let picked_idx = random_index(high_bound);
+                match self.postifx_symbol_list.iter().nth(picked_idx + 1)
+                {
+                    Some(ExpressionSymbol::VARIABLE(color)) => Ok(color.clone()),
+                    Some(ExpressionSymbol::CONSTANT(color_idx)) => Ok(palette_color(*color_idx)),
+                    _ => Err(ExpressionError::INVALID_COLOR)
                 }
-                _ => {}
             }
+            ExpressionSymbol::VARIABLE(color) if !variables.contains_key(color) && !globals.contains_key(color) => Ok(color.clone()),
+            _ => Ok(palette_color(self.evaluate(variables, globals)?))
         }
+    }
 
+    // Variables are looked up in the procedure scope first and then in the globals
+    pub fn evaluate(&self, variables: &HashMap<String, f32>, globals: &HashMap<String, f32>) -> Result<f32, ExpressionError>
+    {
         let mut stack: Vec<f32> = vec!();
         for symbol in self.postifx_symbol_list.iter()
         {
@@ -104,14 +171,20 @@ impl Expression
                 }
                 ExpressionSymbol::VARIABLE(variable) =>
                 {
-                    // println!("{variable}");
-                    // println!("{:?}", variables);
-                    stack.push(*variables.get(variable).unwrap());
+                    let value = variables.get(variable).or_else(|| globals.get(variable)).ok_or_else(|| ExpressionError::UNKNOWN_VARIABLE(variable.clone()))?;
+                    stack.push(*value);
+                }
+                ExpressionSymbol::INVALID_NUMBER(number) =>
+                {
+                    return Err(ExpressionError::INVALID_NUMBER(number.clone()));
                 }
                 ExpressionSymbol::OPERATOR(operator) =>
                 {
-                    let y = stack.pop().unwrap();
-                    let x = stack.pop().unwrap();
+                    let (Some(y), Some(x)) = (stack.pop(), stack.pop())
+                    else
+                    {
+                        return Err(ExpressionError::MISSING_OPERAND(operator.clone()));
+                    };
 
                     match operator.as_str()
                     {
@@ -123,10 +196,15 @@ impl Expression
                         _ => {}
                     }
                 }
+                ExpressionSymbol::FUNCTION_COMMAND(function_command) if function_command == "random" =>
+                {
+                    let high_bound = stack.pop().ok_or(ExpressionError::EMPTY)?;
+                    stack.push(random_number(high_bound)?);
+                }
                 _ => {}
             }
         }
-        stack.pop().unwrap()
+        stack.pop().ok_or(ExpressionError::EMPTY)
     }
 
     fn create_list_of_expression_symbols(expression_str: Vec<&str>) -> LinkedList<ExpressionSymbol>
@@ -148,7 +226,11 @@ impl Expression
             }
             else if expression_symbol.chars().all(|c| c.is_numeric() || c == '.' || c == '-')
             { // Constant
-                result.push_back(ExpressionSymbol::CONSTANT(expression_symbol.parse::<f32>().unwrap()));
+                match expression_symbol.parse::<f32>()
+                {
+                    Ok(constant) => result.push_back(ExpressionSymbol::CONSTANT(constant)),
+                    Err(_) => result.push_back(ExpressionSymbol::INVALID_NUMBER(expression_symbol.to_string()))
+                }
             }
             else if FUNCTION_COMMANDS.contains(expression_symbol)
             {
@@ -175,11 +257,12 @@ impl Expression
                 {
                     postfix.push_back(symbol.clone());
                 }
-                ExpressionSymbol::VARIABLE(_) =>
+                ExpressionSymbol::VARIABLE(_) | ExpressionSymbol::INVALID_NUMBER(_) =>
                 {
                     postfix.push_back(symbol.clone());
                 }
-                ExpressionSymbol::BRACKET_OPENING =>
+                // random applies to everything after it, it only leaves the stack at a closing bracket or at the end
+                ExpressionSymbol::BRACKET_OPENING | ExpressionSymbol::FUNCTION_COMMAND(_) =>
                 {
                     stack.push(symbol.clone());
                 }
@@ -200,20 +283,15 @@ impl Expression
                     {
                         match last_symbol
                         {
-                            ExpressionSymbol::OPERATOR(operator2) =>
+                            ExpressionSymbol::OPERATOR(operator2) if OPERATORS.get(operator1.as_str()) <= OPERATORS.get(operator2.as_str()) =>
                             { 
-                                if OPERATORS.get(operator1.as_str()) <= OPERATORS.get(operator2.as_str())
-                                {
-                                    postfix.push_back(stack.pop().unwrap());
-                                }
-                                else { break; }
+                                postfix.push_back(stack.pop().unwrap());
                             }
                             _ => { break; }
                         }
                     }
                     stack.push(symbol.clone());
                 }
-                _ => {}
             }
         }
         while let Some(remaining_symbol) = stack.pop()
@@ -224,12 +302,365 @@ impl Expression
         postfix
     }
 
+    // In postfix order, or as written for pick
+    pub fn symbols(&self) -> &LinkedList<ExpressionSymbol>
+    {
+        &self.postifx_symbol_list
+    }
+
     pub fn text_literal(&self) -> String
     {
-        if let ExpressionSymbol::VARIABLE(text) = self.postifx_symbol_list.front().unwrap()
+        if let Some(ExpressionSymbol::VARIABLE(text)) = self.postifx_symbol_list.front()
         {
             return text.clone();
         }
         "".to_string()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::parser::read_expression;
+    use proptest::prelude::*;
+
+    // Expression as the parser reads it from the parameter of a command
+    fn parse(code: &str) -> Expression
+    {
+        let symbols = read_expression(&mut format!("{code} ").chars().peekable());
+        Expression::new(symbols.iter().map(|symbol| symbol.as_str()).collect())
+    }
+
+    fn evaluate(code: &str) -> f32
+    {
+        parse(code).evaluate(&HashMap::new(), &HashMap::new()).unwrap()
+    }
+
+    fn evaluation_error(code: &str) -> ExpressionError
+    {
+        parse(code).evaluate(&HashMap::new(), &HashMap::new()).unwrap_err()
+    }
+
+    fn symbols(expression: &Expression) -> Vec<ExpressionSymbol>
+    {
+        expression.postifx_symbol_list.iter().cloned().collect()
+    }
+
+    fn constant(value: f32) -> ExpressionSymbol
+    {
+        ExpressionSymbol::CONSTANT(value)
+    }
+
+    fn operator(operator: &str) -> ExpressionSymbol
+    {
+        ExpressionSymbol::OPERATOR(operator.to_string())
+    }
+
+    fn variables(pairs: &[(&str, f32)]) -> HashMap<String, f32>
+    {
+        pairs.iter().map(|(name, value)| (name.to_string(), *value)).collect()
+    }
+
+    #[test]
+    fn symbols_are_classified()
+    {
+        let infix = Expression::create_list_of_expression_symbols(vec!["(", "size", "+", "2.5", ")", "*", "-3", "<", "random", "-"]);
+        assert_eq!(infix.into_iter().collect::<Vec<_>>(),
+                   vec![ExpressionSymbol::BRACKET_OPENING, ExpressionSymbol::VARIABLE("size".to_string()), operator("+"), constant(2.5), ExpressionSymbol::BRACKET_CLOSING,
+                        operator("*"), constant(-3.0), operator("<"), ExpressionSymbol::FUNCTION_COMMAND("random".to_string()), operator("-")]);
+    }
+
+    // Anything made of digits, dots and minus signs is taken for a number
+    #[test]
+    fn minus_signs_within_a_number_make_an_invalid_number()
+    {
+        let infix = Expression::create_list_of_expression_symbols(vec!["1-", "..", "1.2.3"]);
+        assert_eq!(infix.into_iter().collect::<Vec<_>>(), ["1-", "..", "1.2.3"].map(|number| ExpressionSymbol::INVALID_NUMBER(number.to_string())));
+    }
+
+    #[test]
+    fn subtraction_without_spaces_is_an_error()
+    {
+        assert_eq!(evaluation_error("4-2"), ExpressionError::INVALID_NUMBER("4-2".to_string()));
+    }
+
+    #[test]
+    fn postfix_follows_precedence()
+    {
+        assert_eq!(symbols(&parse("2 + 3 * 4")), vec![constant(2.0), constant(3.0), constant(4.0), operator("*"), operator("+")]);
+        assert_eq!(symbols(&parse("(2 + 3) * 4")), vec![constant(2.0), constant(3.0), operator("+"), constant(4.0), operator("*")]);
+        assert_eq!(symbols(&parse("8 - 3 - 2")), vec![constant(8.0), constant(3.0), operator("-"), constant(2.0), operator("-")]);
+        assert_eq!(symbols(&parse("3 < 1 + 1")), vec![constant(3.0), constant(1.0), constant(1.0), operator("+"), operator("<")]);
+    }
+
+    #[test]
+    fn arithmetic()
+    {
+        assert_eq!(evaluate("2 + 3 * 4"), 14.0);
+        assert_eq!(evaluate("(2+3)*4"), 20.0);
+        assert_eq!(evaluate("8 - 3 - 2"), 3.0);
+        assert_eq!(evaluate("16 / 4 / 2"), 2.0);
+        assert_eq!(evaluate("0.5 * 3"), 1.5);
+        assert_eq!(evaluate("((7))"), 7.0);
+        assert_eq!(evaluate("1 / 0"), f32::INFINITY);
+    }
+
+    #[test]
+    fn comparison_binds_loosest()
+    {
+        assert_eq!(evaluate("1 + 1 < 3"), 1.0);
+        assert_eq!(evaluate("3 < 1 + 1"), 0.0);
+        assert_eq!(evaluate("2 < 2"), 0.0);
+    }
+
+    #[test]
+    fn negative_numbers()
+    {
+        assert_eq!(evaluate("-2"), -2.0);
+        assert_eq!(evaluate("2 * -3"), -6.0);
+        assert_eq!(evaluate("-2 - -3"), 1.0);
+        assert_eq!(evaluate("4 -2"), 2.0);
+        assert_eq!(evaluate("(2 + 3) -1"), 4.0);
+    }
+
+    #[test]
+    fn unbalanced_brackets_are_tolerated()
+    {
+        assert_eq!(evaluate("2 + 3 )"), 5.0);
+        assert_eq!(evaluate("( 2 + 3"), 5.0);
+    }
+
+    #[test]
+    fn variables_are_looked_up_in_the_procedure_before_the_globals()
+    {
+        let expression = parse(":size * 2 + :who");
+        assert_eq!(expression.evaluate(&variables(&[("size", 10.0)]), &variables(&[("who", 3.0), ("size", 100.0)])), Ok(23.0));
+        assert_eq!(expression.evaluate(&HashMap::new(), &variables(&[("who", 3.0), ("size", 100.0)])), Ok(203.0));
+    }
+
+    #[test]
+    fn unknown_variable_is_an_error()
+    {
+        assert_eq!(evaluation_error(":missing + 1"), ExpressionError::UNKNOWN_VARIABLE("missing".to_string()));
+    }
+
+    #[test]
+    fn empty_expression_is_an_error()
+    {
+        let no_variables = HashMap::new();
+        assert_eq!(Expression::new(vec!()).evaluate(&no_variables, &no_variables), Err(ExpressionError::EMPTY));
+        assert_eq!(Expression::new(vec!()).evaluate_color(&no_variables, &no_variables), Err(ExpressionError::EMPTY));
+        assert_eq!(Expression::new(vec!()).text_literal(), "");
+    }
+
+    #[test]
+    fn missing_operand_is_an_error()
+    {
+        assert_eq!(evaluation_error("1 +"), ExpressionError::MISSING_OPERAND("+".to_string()));
+        assert_eq!(evaluation_error("* 2"), ExpressionError::MISSING_OPERAND("*".to_string()));
+        assert_eq!(evaluation_error("( )"), ExpressionError::EMPTY);
+    }
+
+    #[test]
+    fn random_stays_below_its_bound_and_follows_the_seed()
+    {
+        seed_random(7);
+        let first: Vec<f32> = (0..100).map(|_| evaluate("random 10")).collect();
+        assert!(first.iter().all(|number| (0.0..10.0).contains(number) && number.fract() == 0.0));
+        seed_random(7);
+        let second: Vec<f32> = (0..100).map(|_| evaluate("random 10")).collect();
+        assert_eq!(first, second);
+        assert!((0..20).all(|_| evaluate("random 1") == 0.0));
+    }
+
+    #[test]
+    fn random_below_one_is_an_error()
+    {
+        assert_eq!(evaluation_error("random 0"), ExpressionError::INVALID_RANDOM_BOUND(0.0));
+        assert_eq!(evaluation_error("random 0.5"), ExpressionError::INVALID_RANDOM_BOUND(0.5));
+        assert_eq!(evaluation_error("random -3"), ExpressionError::INVALID_RANDOM_BOUND(-3.0));
+        assert_eq!(Expression::new(vec!["random"]).evaluate(&HashMap::new(), &HashMap::new()), Err(ExpressionError::EMPTY));
+    }
+
+    // random takes everything after it as its bound, wherever it appears
+    #[test]
+    fn random_takes_the_rest_of_the_expression()
+    {
+        assert!((0..50).all(|_| evaluate("random 2 - 1") == 0.0));
+        assert!((0..50).all(|_| evaluate("random 2 + 100") < 102.0));
+        assert_eq!(symbols(&parse("random 2 + 3")), vec![constant(2.0), constant(3.0), operator("+"), ExpressionSymbol::FUNCTION_COMMAND("random".to_string())]);
+    }
+
+    #[test]
+    fn random_after_an_operator()
+    {
+        assert_eq!(evaluate("1 + random 1"), 1.0);
+        assert_eq!(evaluate("(random 1) + 5"), 5.0);
+        assert_eq!(Expression::new(vec!["1", "+", "random", "1"]).evaluate(&HashMap::new(), &HashMap::new()), Ok(1.0));
+        assert_eq!(evaluation_error("1 + random 0"), ExpressionError::INVALID_RANDOM_BOUND(0.0));
+    }
+
+    #[test]
+    fn random_of_a_variable()
+    {
+        let variables = HashMap::from([(String::from("n"), 1.0)]);
+        assert_eq!(parse("random :n").evaluate(&variables, &HashMap::new()), Ok(0.0));
+    }
+
+    #[test]
+    fn colors()
+    {
+        let no_variables = HashMap::new();
+        assert_eq!(parse("\"red").evaluate_color(&no_variables, &no_variables), Ok("red".to_string()));
+        assert_eq!(parse("4").evaluate_color(&no_variables, &no_variables), Ok("red".to_string()));
+        assert_eq!(parse("14 + 2").evaluate_color(&no_variables, &no_variables), Ok("black".to_string()));
+        assert_eq!(parse("2.6").evaluate_color(&no_variables, &no_variables), Ok("cyan".to_string()));
+        assert_eq!(parse(":color").evaluate_color(&variables(&[("color", 1.0)]), &no_variables), Ok("blue".to_string()));
+        assert_eq!(parse(":who").evaluate_color(&no_variables, &variables(&[("who", 6.0)])), Ok("yellow".to_string()));
+    }
+
+    #[test]
+    fn pick_chooses_one_of_the_colors()
+    {
+        let no_variables = HashMap::new();
+        let expression = parse("pick [red green blue]");
+        seed_random(3);
+        let picked: HashSet<String> = (0..100).map(|_| expression.evaluate_color(&no_variables, &no_variables).unwrap()).collect();
+        assert_eq!(picked, ["red", "green", "blue"].iter().map(|color| color.to_string()).collect());
+    }
+
+    #[test]
+    fn pick_takes_numbers_from_the_palette()
+    {
+        let no_variables = HashMap::new();
+        assert_eq!(parse("pick [4]").evaluate_color(&no_variables, &no_variables), Ok("red".to_string()));
+        assert_eq!(Expression::new(vec!["pick"]).evaluate_color(&no_variables, &no_variables), Err(ExpressionError::EMPTY_PICK));
+        assert_eq!(Expression::new(vec!["pick", "("]).evaluate_color(&no_variables, &no_variables), Err(ExpressionError::INVALID_COLOR));
+    }
+
+    #[test]
+    fn text_literal()
+    {
+        assert_eq!(parse("\"hello").text_literal(), "hello");
+        assert_eq!(parse("12").text_literal(), "");
+    }
+
+    // Arithmetic on small integers, with the same rounding as the interpreter when evaluated in f32
+    #[derive(Clone, Debug)]
+    enum Node
+    {
+        NUMBER(i16),
+        OPERATION(Box<Node>, char, Box<Node>)
+    }
+
+    fn precedence(operator: char) -> u8
+    {
+        match operator
+        {
+            '<' => 0,
+            '+' | '-' => 1,
+            _ => 2
+        }
+    }
+
+    fn reference_evaluate(node: &Node) -> f32
+    {
+        match node
+        {
+            Node::NUMBER(number) => *number as f32,
+            Node::OPERATION(left, operator, right) =>
+            {
+                let (x, y) = (reference_evaluate(left), reference_evaluate(right));
+                match operator
+                {
+                    '+' => x + y,
+                    '-' => x - y,
+                    '*' => x * y,
+                    '/' => x / y,
+                    _ => if x < y { 1.0 } else { 0.0 }
+                }
+            }
+        }
+    }
+
+    // Source with only the brackets needed to keep the shape of the tree, or with brackets around every operation
+    fn source(node: &Node, all_brackets: bool) -> String
+    {
+        match node
+        {
+            Node::NUMBER(number) => number.to_string(),
+            Node::OPERATION(left, operator, right) =>
+            {
+                let operand = |child: &Node, is_right: bool|
+                {
+                    let needs_brackets = match child
+                    {
+                        Node::NUMBER(_) => false,
+                        Node::OPERATION(_, child_operator, _) => all_brackets || precedence(*child_operator) < precedence(*operator) ||
+                                                                 (is_right && precedence(*child_operator) == precedence(*operator))
+                    };
+                    if needs_brackets { format!("( {} )", source(child, all_brackets)) } else { source(child, all_brackets) }
+                };
+                format!("{} {} {}", operand(left, false), operator, operand(right, true))
+            }
+        }
+    }
+
+    fn arithmetic_tree() -> impl Strategy<Value = Node>
+    {
+        (-50i16..50).prop_map(Node::NUMBER).prop_recursive(6, 40, 2, |inner|
+        {
+            (inner.clone(), prop::sample::select(vec!['+', '-', '*', '/', '<']), inner)
+                .prop_map(|(left, operator, right)| Node::OPERATION(Box::new(left), operator, Box::new(right)))
+        })
+    }
+
+    fn same_number(x: f32, y: f32) -> bool
+    {
+        x == y || (x.is_nan() && y.is_nan())
+    }
+
+    proptest!
+    {
+        #[test]
+        fn evaluation_matches_the_reference(tree in arithmetic_tree())
+        {
+            let code = source(&tree, false);
+            prop_assert!(same_number(evaluate(&code), reference_evaluate(&tree)), "{} gave {} instead of {}", code, evaluate(&code), reference_evaluate(&tree));
+        }
+
+        #[test]
+        fn redundant_brackets_change_nothing(tree in arithmetic_tree())
+        {
+            prop_assert!(same_number(evaluate(&source(&tree, true)), evaluate(&source(&tree, false))));
+        }
+
+        #[test]
+        fn operations_of_the_same_precedence_go_left_to_right(numbers in prop::collection::vec(1i16..100, 1..10), additive in any::<bool>(), inverse in prop::collection::vec(any::<bool>(), 9))
+        {
+            let mut code = numbers[0].to_string();
+            let mut expected = numbers[0] as f32;
+            for (number, inverse) in numbers[1..].iter().zip(&inverse)
+            {
+                let operator = match (additive, inverse) { (true, false) => '+', (true, true) => '-', (false, false) => '*', (false, true) => '/' };
+                code.push_str(&format!(" {operator} {number}"));
+                expected = match operator
+                {
+                    '+' => expected + *number as f32,
+                    '-' => expected - *number as f32,
+                    '*' => expected * *number as f32,
+                    _ => expected / *number as f32
+                };
+            }
+            prop_assert!(same_number(evaluate(&code), expected), "{} gave {} instead of {}", code, evaluate(&code), expected);
+        }
+
+        #[test]
+        fn random_is_within_its_bound(bound in 1i32..10000)
+        {
+            let number = evaluate(&format!("random {bound}"));
+            prop_assert!(number >= 0.0 && number < bound as f32 && number.fract() == 0.0);
+        }
+    }
+}
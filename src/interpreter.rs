@@ -1,7 +1,63 @@
-use std::collections::{ HashMap, LinkedList };
-use crate::parser::{ CodeBlock, ParserSymbol, ProcedureCall, CodeBlockType, CommandType };
+use std::collections::{ linked_list, BTreeMap, HashMap };
+use std::fmt;
+use std::time::{ Duration, Instant };
+use crate::parser::{ CodeBlock, ParserSymbol, CodeBlockType, CommandType };
+use crate::drawing::{ Drawing, DrawingElement, Segment, Style, Text, Sprite, PenMode, ActionKind, TurtleAction, TURTLE_SHAPES, DEFAULT_TURTLE_SHAPE };
+use crate::expression::{ seed_random, ExpressionError };
 use std::f32::consts::PI;
 
+mod bytecode;
+mod vm;
+
+#[derive(Debug)]
+pub enum RuntimeError
+{
+    TURTLE_OUT_OF_BOUNDS,
+    UNKNOWN_SHAPE(String),
+    INVALID_TURTLE(i64, usize),
+    INSTRUCTION_LIMIT(u64),
+    DEPTH_LIMIT(usize),
+    PRIMITIVE_LIMIT(usize),
+    TIMEOUT(Duration),
+    UNKNOWN_PROCEDURE(String),
+    INVALID_DISTANCE(f32),
+    EXPRESSION(ExpressionError)
+}
+impl fmt::Display for RuntimeError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            RuntimeError::TURTLE_OUT_OF_BOUNDS => write!(f, "turtle out of bounds"),
+            RuntimeError::UNKNOWN_SHAPE(shape) => write!(f, "unknown turtle shape: {shape}"),
+            RuntimeError::INVALID_TURTLE(turtle, max_turtles) => write!(f, "turtle {turtle} does not exist, turtles are numbered from 1 to {max_turtles}"),
+            RuntimeError::INSTRUCTION_LIMIT(max_instructions) => write!(f, "more than {max_instructions} instructions run"),
+            RuntimeError::DEPTH_LIMIT(max_depth) => write!(f, "more than {max_depth} procedures running at once"),
+            RuntimeError::PRIMITIVE_LIMIT(max_primitives) => write!(f, "more than {max_primitives} lines, shapes and labels drawn"),
+            RuntimeError::TIMEOUT(timeout) => write!(f, "still running after {} seconds", timeout.as_secs_f32()),
+            RuntimeError::UNKNOWN_PROCEDURE(procedure_name) => write!(f, "procedure {procedure_name} is not defined, its definition must end before the next one starts"),
+            RuntimeError::INVALID_DISTANCE(distance) => write!(f, "turtle cannot move by {distance}"),
+            RuntimeError::EXPRESSION(error) => write!(f, "{error}")
+        }
+    }
+}
+impl From<ExpressionError> for RuntimeError
+{
+    fn from(error: ExpressionError) -> Self
+    {
+        RuntimeError::EXPRESSION(error)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum BoundaryMode
+{
+    WRAP,   // leaving the canvas through one edge re-enters through the opposite one
+    FENCE,  // leaving the canvas is an error
+    WINDOW  // the canvas is unbounded
+}
+
 struct Turtle
 {
     x: f32,
@@ -9,11 +65,20 @@ struct Turtle
     dir_x: f32,
     dir_y: f32,
     lifted: bool,
+    pen_mode: PenMode,
     label_height: i32,
-    stroke_color: String
+    stroke_color: String,
+    visible: bool,
+    shape: String  // SVG markup
 }
 impl Turtle
 {
+    pub fn new(canvas_width: i32, canvas_height: i32) -> Self
+    {
+        Self { x: canvas_width as f32 / 2.0, y: canvas_height as f32 / 2.0, dir_x: 0.0, dir_y: -1.0, lifted: false, pen_mode: PenMode::PAINT, label_height: 100, stroke_color: "black".to_string(),
+               visible: true, shape: TURTLE_SHAPES[DEFAULT_TURTLE_SHAPE].to_string() }
+    }
+
     pub fn rotate_right(&mut self, turn_degrees: f32)
     {
         let turn_rad = turn_degrees * PI / 180.0;
@@ -22,50 +87,545 @@ impl Turtle
         self.dir_x = new_angle.cos();
         self.dir_y = new_angle.sin();
     }
+
+    pub fn style(&self) -> Style
+    {
+        Style{ stroke_color: self.stroke_color.clone(), stroke_width: 1.0, fill_color: None, pen_mode: self.pen_mode }
+    }
+
+    // Degrees clockwise from up
+    pub fn heading(&self) -> f32
+    {
+        self.dir_x.atan2(-self.dir_y).to_degrees().rem_euclid(360.0)
+    }
+
+    pub fn action(&self, turtle: usize, kind: ActionKind, from: (f32, f32), to: (f32, f32)) -> TurtleAction
+    {
+        TurtleAction{ kind, turtle, tick: 0, from, to, heading: self.heading(), pen_down: !self.lifted, pen_mode: self.pen_mode,
+                      color: self.stroke_color.clone(), width: 1.0 }
+    }
+
+    pub fn home(&mut self, canvas_width: i32, canvas_height: i32)
+    {
+        self.x = canvas_width as f32 / 2.0;
+        self.y = canvas_height as f32 / 2.0;
+        self.dir_x = 0.0;
+        self.dir_y = -1.0;
+    }
 }
 
-pub fn execute_logo_program(procedures: HashMap<String, CodeBlock>) -> String
+// Far deeper than drawings go, a recursion reaching it most likely misses its stop. Tail calls do not count
+pub const DEFAULT_MAX_DEPTH: usize = 10000;
+
+#[derive(Clone)]
+pub struct InterpreterOptions
+{
+    pub canvas_width: i32,
+    pub canvas_height: i32,
+    pub max_turtles: usize,
+    pub seed: Option<u64>,               // makes random and pick give the same numbers on every run
+    pub max_instructions: Option<u64>,   // instructions and loop iterations of a run before stopping with an error
+    pub max_depth: Option<usize>,        // procedures running at once, the main program included
+    pub max_primitives: Option<usize>,   // lines, shapes and labels drawn before stopping with an error, cleared ones included
+    pub timeout: Option<Duration>,       // time a run may take
+    pub shapes: HashMap<String, String>  // SVG markup of shapes setshape accepts besides the built-in ones, by name
+}
+impl Default for InterpreterOptions
+{
+    fn default() -> Self
+    {
+        Self { canvas_width: 1100, canvas_height: 600, max_turtles: 100, seed: None, max_instructions: None, max_depth: Some(DEFAULT_MAX_DEPTH), max_primitives: None, timeout: None,
+               shapes: HashMap::new() }
+    }
+}
+
+struct InterpreterState
 {
-    let canvas_width = 1100;
-    let canvas_height = 600;
-    //let canvas_offset = 50;
-    let mut turtles: Vec<Turtle> = vec!
-    [
-        Turtle{ x: canvas_width as f32 / 2.0, y: canvas_height as f32 / 2.0, dir_x: 0.0, dir_y: -1.0, lifted: false, label_height: 100, stroke_color: "black".to_string() },
-        Turtle{ x: canvas_width as f32 / 2.0, y: canvas_height as f32 / 2.0, dir_x: 0.0, dir_y: -1.0, lifted: false, label_height: 100, stroke_color: "black".to_string() }
-    ];
-    let mut svg = format!("<svg width=\"{}\" height=\"{}\" xmlns=\"http://www.w3.org/2000/svg\">\n\t<rect width=\"100%\" height=\"100%\" style=\"fill:rgb(255,255,255);stroke-width:10;stroke:rgb(0,0,0)\" />", canvas_width, canvas_height);
+    drawing: Drawing,
+    turtles: BTreeMap<usize, Turtle>,
+    current_turtle: usize,
+    max_turtles: usize,
+    boundary_mode: BoundaryMode,
+    globals: HashMap<String, f32>,
+    instructions: u64,
+    max_instructions: Option<u64>,
+    depth: usize,
+    max_depth: Option<usize>,
+    primitives: usize,
+    max_primitives: Option<usize>,
+    deadline: Option<Instant>,
+    timeout: Option<Duration>,
+    shapes: HashMap<String, String>
+}
+impl InterpreterState
+{
+    fn turtle(&mut self) -> &mut Turtle
+    {
+        self.turtles.get_mut(&self.current_turtle).unwrap()
+    }
+
+    // Makes the given turtle the current one, creating it at home if it is used for the first time
+    fn select_turtle(&mut self, turtle: i64) -> Result<(), RuntimeError>
+    {
+        if turtle < 1 || turtle > self.max_turtles as i64
+        {
+            return Err(RuntimeError::INVALID_TURTLE(turtle, self.max_turtles));
+        }
+        let (width, height) = (self.drawing.width, self.drawing.height);
+        self.turtles.entry(turtle as usize).or_insert_with(|| Turtle::new(width, height));
+        self.current_turtle = turtle as usize;
 
-    let MAIN_PROCEDURE_NAME = "_".to_string(); // this should be taken from parser
-    let main_procedure = ParserSymbol::PROCEDURE_CALL(ProcedureCall::new(MAIN_PROCEDURE_NAME, LinkedList::new()));
-    execute_instruction(&main_procedure, &mut svg, &procedures,  &mut turtles, &mut 0, &mut HashMap::new(), &mut false);
+        self.globals.insert("who".to_string(), turtle as f32);
+        self.globals.insert("turtles".to_string(), self.turtles.len() as f32);
+        Ok(())
+    }
+
+    // Records what the current turtle did, moves give where it went from and to
+    fn record_action(&mut self, kind: ActionKind, from: Option<(f32, f32)>)
+    {
+        let turtle = &self.turtles[&self.current_turtle];
+        let position = (turtle.x, turtle.y);
+        let action = turtle.action(self.current_turtle, kind, from.unwrap_or(position), position);
+        self.drawing.add_action(action);
+    }
+
+    // Counts an instruction or a loop iteration, and checks the time as every endless program goes through here
+    fn step(&mut self) -> Result<(), RuntimeError>
+    {
+        self.instructions += 1;
+        if let Some(max_instructions) = self.max_instructions.filter(|max_instructions| self.instructions > *max_instructions)
+        {
+            return Err(RuntimeError::INSTRUCTION_LIMIT(max_instructions));
+        }
+        if let (Some(deadline), Some(timeout)) = (self.deadline, self.timeout)
+        {
+            if Instant::now() > deadline
+            {
+                return Err(RuntimeError::TIMEOUT(timeout));
+            }
+        }
+        Ok(())
+    }
 
-    format!("{svg}\n</svg>")
+    fn draw(&mut self, element: DrawingElement) -> Result<(), RuntimeError>
+    {
+        self.primitives += 1;
+        if let Some(max_primitives) = self.max_primitives.filter(|max_primitives| self.primitives > *max_primitives)
+        {
+            return Err(RuntimeError::PRIMITIVE_LIMIT(max_primitives));
+        }
+        self.drawing.add_element(element);
+        Ok(())
+    }
+
+    fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) -> Result<(), RuntimeError>
+    {
+        let turtle = &self.turtles[&self.current_turtle];
+        self.drawing.add_action(turtle.action(self.current_turtle, ActionKind::MOVE, (x1, y1), (x2, y2)));
+        if !turtle.lifted
+        {
+            let segment = Segment{ x1, y1, x2, y2, style: turtle.style() };
+            self.draw(DrawingElement::SEGMENT(segment))?;
+        }
+        Ok(())
+    }
+
+    // Moves the current turtle along its heading (backwards for negative distance), honouring the boundary mode
+    fn move_turtle(&mut self, distance: f32) -> Result<(), RuntimeError>
+    {
+        if !distance.is_finite()
+        {
+            return Err(RuntimeError::INVALID_DISTANCE(distance));
+        }
+        let width = self.drawing.width as f32;
+        let height = self.drawing.height as f32;
+        let turtle = &self.turtles[&self.current_turtle];
+        let (mut x, mut y) = (turtle.x, turtle.y);
+        let step_x = turtle.dir_x * distance.signum();
+        let step_y = turtle.dir_y * distance.signum();
+        let mut remaining = distance.abs();
+
+        match self.boundary_mode
+        {
+            BoundaryMode::WINDOW =>
+            {
+                self.draw_line(x, y, x + step_x * remaining, y + step_y * remaining)?;
+                x += step_x * remaining;
+                y += step_y * remaining;
+            }
+            BoundaryMode::FENCE =>
+            {
+                let new_x = x + step_x * remaining;
+                let new_y = y + step_y * remaining;
+                if new_x < 0.0 || new_x > width || new_y < 0.0 || new_y > height
+                {
+                    return Err(RuntimeError::TURTLE_OUT_OF_BOUNDS);
+                }
+                self.draw_line(x, y, new_x, new_y)?;
+                x = new_x;
+                y = new_y;
+            }
+            BoundaryMode::WRAP =>
+            {
+                // A turtle left outside the canvas, in window mode before, comes back in where it would have wrapped to
+                if !(0.0..=width).contains(&x) { x = x.rem_euclid(width); }
+                if !(0.0..=height).contains(&y) { y = y.rem_euclid(height); }
+                while remaining > 0.0
+                {
+                    // Distance left until the turtle crosses a vertical or a horizontal edge
+                    let edge_distance_x = if step_x > 0.0 { ((width - x) / step_x).max(0.0) } else if step_x < 0.0 { (-x / step_x).max(0.0) } else { f32::INFINITY };
+                    let edge_distance_y = if step_y > 0.0 { ((height - y) / step_y).max(0.0) } else if step_y < 0.0 { (-y / step_y).max(0.0) } else { f32::INFINITY };
+                    let segment_length = remaining.min(edge_distance_x).min(edge_distance_y);
+
+                    let new_x = x + step_x * segment_length;
+                    let new_y = y + step_y * segment_length;
+                    if segment_length > 0.0
+                    {
+                        self.draw_line(x, y, new_x, new_y)?;
+                    }
+                    x = new_x;
+                    y = new_y;
+                    // So far that wrapping no longer brings it any closer with the precision of f32
+                    if segment_length > 0.0 && remaining - segment_length == remaining
+                    {
+                        return Err(RuntimeError::INVALID_DISTANCE(distance));
+                    }
+                    remaining -= segment_length;
+
+                    if remaining > 0.0
+                    {
+                        // Each time round the canvas counts as an instruction, long moves are bounded by the limit
+                        self.step()?;
+                        if segment_length == edge_distance_x { x = if step_x > 0.0 { 0.0 } else { width }; }
+                        if segment_length == edge_distance_y { y = if step_y > 0.0 { 0.0 } else { height }; }
+                    }
+                }
+            }
+        }
+
+        let turtle = self.turtle();
+        turtle.x = x;
+        turtle.y = y;
+        Ok(())
+    }
+
+    fn turn(&mut self, turn_degrees: f32)
+    {
+        self.turtle().rotate_right(turn_degrees);
+        self.record_action(ActionKind::TURN, None);
+    }
+
+    fn set_pen_mode(&mut self, command_type: CommandType)
+    {
+        let turtle = self.turtle();
+        turtle.lifted = false;
+        turtle.pen_mode = match command_type
+        {
+            CommandType::PEN_ERASE => PenMode::ERASE,
+            CommandType::PEN_REVERSE => PenMode::REVERSE,
+            _ => PenMode::PAINT
+        };
+    }
+
+    fn label(&mut self, text: String) -> Result<(), RuntimeError>
+    {
+        let turtle = self.turtle();
+        let rotation_angle = (turtle.dir_y.atan2(turtle.dir_x) * 180.0 / PI).round();
+        let label = Text{ x: turtle.x, y: turtle.y, color: turtle.stroke_color.clone(), font_size: turtle.label_height, rotation_angle, text: text.clone() };
+        let font_size = label.font_size;
+        self.draw(DrawingElement::TEXT(label))?;
+        self.record_action(ActionKind::LABEL{ text, font_size }, None);
+        Ok(())
+    }
+
+    fn set_background(&mut self, color: String)
+    {
+        self.drawing.background_color = color.clone();
+        self.record_action(ActionKind::SET_BACKGROUND{ color }, None);
+    }
+
+    fn clean(&mut self)
+    {
+        self.drawing.clear();
+        self.record_action(ActionKind::CLEAR, None);
+    }
+
+    fn clear_screen(&mut self)
+    {
+        self.clean();
+        let (width, height) = (self.drawing.width, self.drawing.height);
+        for (id, turtle) in self.turtles.iter_mut()
+        {
+            let from = (turtle.x, turtle.y);
+            turtle.home(width, height);
+            self.drawing.add_action(turtle.action(*id, ActionKind::HOME, from, (turtle.x, turtle.y)));
+        }
+    }
+
+    fn set_visible(&mut self, visible: bool)
+    {
+        self.turtle().visible = visible;
+        self.record_action(if visible { ActionKind::SHOW } else { ActionKind::HIDE }, None);
+    }
+
+    fn set_shape(&mut self, shape: String) -> Result<(), RuntimeError>
+    {
+        let markup = match self.shapes.get(&shape)
+        {
+            Some(markup) => markup.clone(),
+            None => TURTLE_SHAPES.get(shape.as_str()).ok_or(RuntimeError::UNKNOWN_SHAPE(shape))?.to_string()
+        };
+        self.turtle().shape = markup;
+        Ok(())
+    }
+
+    fn wait(&mut self, ticks: f32)
+    {
+        let ticks = ticks.max(0.0).round() as u32;
+        self.record_action(ActionKind::WAIT{ ticks }, None);
+        self.drawing.wait(ticks);
+    }
+}
+
+// Program run in pieces, the turtles, procedures and drawing carry over from one piece to the next
+pub struct Session
+{
+    state: InterpreterState,
+    procedures: HashMap<String, CodeBlock>
+}
+impl Session
+{
+    pub fn new(options: &InterpreterOptions) -> Result<Self, RuntimeError>
+    {
+        let mut state = InterpreterState{ drawing: Drawing::new(options.canvas_width, options.canvas_height), turtles: BTreeMap::new(), current_turtle: 1, max_turtles: options.max_turtles,
+                                          boundary_mode: BoundaryMode::WINDOW, globals: HashMap::new(),
+                                          instructions: 0, max_instructions: options.max_instructions, depth: 0, max_depth: options.max_depth,
+                                          primitives: 0, max_primitives: options.max_primitives, deadline: None, timeout: options.timeout,
+                                          shapes: options.shapes.clone() };
+        state.select_turtle(1)?;
+        if let Some(seed) = options.seed
+        {
+            seed_random(seed);
+        }
+        Ok(Self { state, procedures: HashMap::new() })
+    }
+
+    // Runs the main code of the parsed program, its procedures are added to the ones defined before.
+    // The instruction limit and the timeout apply to each run on its own
+    pub fn execute(&mut self, procedures: HashMap<String, CodeBlock>) -> Result<(), RuntimeError>
+    {
+        self.run(procedures, Engine::VM)
+    }
+
+    // Same as execute, walking the parsed program instead of compiling it
+    pub fn walk(&mut self, procedures: HashMap<String, CodeBlock>) -> Result<(), RuntimeError>
+    {
+        self.run(procedures, Engine::TREE_WALKER)
+    }
+
+    fn run(&mut self, procedures: HashMap<String, CodeBlock>, engine: Engine) -> Result<(), RuntimeError>
+    {
+        self.procedures.extend(procedures);
+        self.state.instructions = 0;
+        self.state.deadline = self.state.timeout.map(|timeout| Instant::now() + timeout);
+        let main_procedure_name = "_"; // this should be taken from parser
+        match engine
+        {
+            Engine::VM => vm::run(&bytecode::compile(&self.procedures, main_procedure_name), &mut self.state),
+            Engine::TREE_WALKER =>
+            {
+                let Some(main_procedure) = self.procedures.get(main_procedure_name) else { return Ok(()); };
+                execute_procedure(main_procedure, &self.procedures, &mut self.state)
+            }
+        }
+    }
+
+    // What is drawn so far, with the visible turtles on top
+    pub fn drawing(&self) -> Drawing
+    {
+        let mut drawing = self.state.drawing.clone();
+        for turtle in self.state.turtles.values().filter(|turtle| turtle.visible)
+        {
+            let rotation_angle = turtle.dir_y.atan2(turtle.dir_x) * 180.0 / PI + 90.0;
+            drawing.add_sprite(Sprite{ x: turtle.x, y: turtle.y, color: turtle.stroke_color.clone(), rotation_angle, shape: turtle.shape.clone() });
+        }
+        drawing
+    }
+}
+
+pub fn execute_logo_program(procedures: HashMap<String, CodeBlock>, options: &InterpreterOptions) -> Result<Drawing, RuntimeError>
+{
+    let mut session = Session::new(options)?;
+    session.execute(procedures)?;
+    Ok(session.drawing())
+}
+
+// Runs the program on the tree-walker, which the compiled program must match. Kept to compare both
+pub fn walk_logo_program(procedures: HashMap<String, CodeBlock>, options: &InterpreterOptions) -> Result<Drawing, RuntimeError>
+{
+    let mut session = Session::new(options)?;
+    session.walk(procedures)?;
+    Ok(session.drawing())
+}
+
+// The program is compiled to bytecode run by a virtual machine, the tree-walker below runs the parsed program as it is
+enum Engine
+{
+    VM,
+    TREE_WALKER
+}
+
+// What a frame does when its instructions run out
+enum FrameKind
+{
+    PROCEDURE{ variables: HashMap<String, f32> },  // the scope of the blocks above it, up to the next procedure
+    LOOP{ iteration: i32, repeats: i32 },
+    IF,
+    ASK{ turtles: std::vec::IntoIter<i64>, previous_turtle: usize, restore_turtle: bool }
+}
+
+// A procedure or block being run. The frames are kept on the heap rather than in native recursion,
+// so deep recursion is bounded by the depth limit alone and tail calls can replace the frame of their caller
+struct Frame<'a>
+{
+    code_block: &'a CodeBlock,
+    instructions: linked_list::Iter<'a, ParserSymbol>,
+    kind: FrameKind
+}
+impl<'a> Frame<'a>
+{
+    fn new(code_block: &'a CodeBlock, kind: FrameKind) -> Self
+    {
+        Self { code_block, instructions: code_block.get_instructions().iter(), kind }
+    }
+}
+
+// Variables of the procedure running at the top of the stack
+fn scope<'f>(frames: &'f mut [Frame]) -> &'f mut HashMap<String, f32>
+{
+    frames.iter_mut().rev().find_map(|frame| match &mut frame.kind
+    {
+        FrameKind::PROCEDURE{ variables } => Some(variables),
+        _ => None
+    }).unwrap()
+}
+
+// A call is in tail position when nothing is left to run after it in its procedure, ifs ending with it included
+fn is_tail_call(frames: &[Frame]) -> bool
+{
+    for frame in frames.iter().rev()
+    {
+        if frame.instructions.len() > 0 { return false; }
+        match frame.kind
+        {
+            FrameKind::PROCEDURE{ .. } => return true,
+            FrameKind::IF => {}
+            _ => return false
+        }
+    }
+    false
+}
+
+fn execute_procedure(main_procedure: &CodeBlock, procedures: &HashMap<String, CodeBlock>, state: &mut InterpreterState) -> Result<(), RuntimeError>
+{
+    let mut frames = vec![Frame::new(main_procedure, FrameKind::PROCEDURE{ variables: HashMap::new() })];
+    state.depth = 1;
+    while let Some(frame) = frames.last_mut()
+    {
+        match frame.instructions.next()
+        {
+            Some(instruction) => execute_instruction(instruction, procedures, state, &mut frames)?,
+            None => end_frame(state, &mut frames)?
+        }
+    }
+    Ok(())
+}
+
+// Starts the block again for the next iteration or turtle, or removes its frame
+fn end_frame(state: &mut InterpreterState, frames: &mut Vec<Frame>) -> Result<(), RuntimeError>
+{
+    let frame = frames.last_mut().unwrap();
+    match &mut frame.kind
+    {
+        FrameKind::LOOP{ iteration, repeats } if *iteration + 1 < *repeats =>
+        {
+            *iteration += 1;
+            let iteration = *iteration;
+            frame.instructions = frame.code_block.get_instructions().iter();
+            state.step()?;
+            scope(frames).insert("repcount".to_string(), iteration as f32);
+        }
+        FrameKind::ASK{ turtles, .. } if turtles.len() > 0 =>
+        {
+            let turtle = turtles.next().unwrap();
+            frame.instructions = frame.code_block.get_instructions().iter();
+            state.select_turtle(turtle)?;
+        }
+        _ =>
+        {
+            let frame = frames.pop().unwrap();
+            close_frame(state, frames, frame)?;
+        }
+    }
+    Ok(())
+}
+
+// Cleans up after a frame left for good, when its block is over or a stop leaves it early
+fn close_frame(state: &mut InterpreterState, frames: &mut [Frame], frame: Frame) -> Result<(), RuntimeError>
+{
+    match frame.kind
+    {
+        FrameKind::PROCEDURE{ .. } => state.depth -= 1,
+        FrameKind::LOOP{ .. } => { scope(frames).remove("repcount"); }
+        FrameKind::IF => {}
+        FrameKind::ASK{ previous_turtle, restore_turtle, .. } =>
+        {
+            // tell leaves the last turtle of the list as the current one
+            if restore_turtle
+            {
+                state.select_turtle(previous_turtle as i64)?;
+            }
+        }
+    }
+    Ok(())
 }
 
-fn execute_instruction(instruction: &ParserSymbol, svg: &mut String, procedures: &HashMap<String, CodeBlock>, turtles: &mut Vec<Turtle>, current_turtle: &mut usize, variables: &mut HashMap<String, f32>, stop: &mut bool)
+fn execute_instruction<'a>(instruction: &'a ParserSymbol, procedures: &'a HashMap<String, CodeBlock>, state: &mut InterpreterState, frames: &mut Vec<Frame<'a>>) -> Result<(), RuntimeError>
 {
+    state.step()?;
+    let variables = scope(frames);
     match instruction
     {
         ParserSymbol::PROCEDURE_CALL(procedure_call) =>
         {
-            println!("PROCEDURE_CALL {}", &procedure_call.procedure_name);
-            //println!("{:?}", procedures);
-            let procedure = procedures.get(&procedure_call.procedure_name).unwrap();
+            // A procedure whose definition is cut short by another one is called without having been stored
+            let procedure = procedures.get(&procedure_call.procedure_name).ok_or_else(|| RuntimeError::UNKNOWN_PROCEDURE(procedure_call.procedure_name.clone()))?;
             let mut procedure_variables: HashMap<String, f32> = HashMap::new();
-            if let CodeBlockType::PROCEDURE(procedure) = &procedure.code_block_type 
+            if let CodeBlockType::PROCEDURE(procedure) = &procedure.code_block_type
             {
                 for call_parameter in procedure.call_parameters.iter().zip(procedure_call.parameter_expressions.iter())
                 {
-                    procedure_variables.insert(call_parameter.0.clone(), call_parameter.1.evaluate(variables));
+                    procedure_variables.insert(call_parameter.0.clone(), call_parameter.1.evaluate(variables, &state.globals)?);
                 }
             }
 
-            for procedure_instruction in procedure.get_instructions()
+            if is_tail_call(frames)
+            {
+                // The frames of the caller are done, the called procedure takes their place
+                while let Some(frame) = frames.pop()
+                {
+                    if let FrameKind::PROCEDURE{ .. } = frame.kind { break; }
+                }
+            }
+            else
             {
-                execute_instruction(procedure_instruction, svg, procedures, turtles, current_turtle,  &mut procedure_variables, stop);
-                if *stop == true { *stop = false; return; }
+                state.depth += 1;
+                if let Some(max_depth) = state.max_depth.filter(|max_depth| state.depth > *max_depth)
+                {
+                    return Err(RuntimeError::DEPTH_LIMIT(max_depth));
+                }
             }
+            frames.push(Frame::new(procedure, FrameKind::PROCEDURE{ variables: procedure_variables }));
         }
         ParserSymbol::CODE_BLOCK(code_block) =>
         {
@@ -73,29 +633,35 @@ fn execute_instruction(instruction: &ParserSymbol, svg: &mut String, procedures:
             {
                 CodeBlockType::LOOP(my_loop) =>
                 {
-                    let repeats = my_loop.repeats.evaluate(variables).round() as i32;
-                    println!("LOOP {}", repeats);
-                    for i in 0..(repeats)
+                    let repeats = my_loop.repeats.evaluate(variables, &state.globals)?.round() as i32;
+                    if repeats > 0
                     {
-                        variables.insert("repcount".to_string(), i as f32);
-                        for loop_instruction in code_block.get_instructions().iter()
-                        {
-                            execute_instruction(loop_instruction, svg, procedures, turtles, current_turtle,  variables, stop);
-                        }
+                        state.step()?;
+                        variables.insert("repcount".to_string(), 0.0);
+                        frames.push(Frame::new(code_block, FrameKind::LOOP{ iteration: 0, repeats }));
+                    }
+                    else
+                    {
+                        variables.remove("repcount");
                     }
-                    variables.remove("repcount");
                 }
                 CodeBlockType::IF(my_if) =>
                 {
-                    let condition = my_if.condition.evaluate(variables) != 0.0;
-                    println!("IF {}", condition);
+                    let condition = my_if.condition.evaluate(variables, &state.globals)? != 0.0;
                     if condition
                     {
-                        for if_instruction in code_block.get_instructions().iter()
-                        {
-                            execute_instruction(if_instruction, svg, procedures, turtles, current_turtle, variables, stop);
-                            if *stop == true { return; }
-                        }
+                        frames.push(Frame::new(code_block, FrameKind::IF));
+                    }
+                }
+                CodeBlockType::ASK(ask) =>
+                {
+                    let turtles = ask.turtles.iter().map(|turtle| Ok(turtle.evaluate(variables, &state.globals)?.round() as i64)).collect::<Result<Vec<i64>, RuntimeError>>()?;
+                    let previous_turtle = state.current_turtle;
+                    let mut turtles = turtles.into_iter();
+                    if let Some(turtle) = turtles.next()
+                    {
+                        state.select_turtle(turtle)?;
+                        frames.push(Frame::new(code_block, FrameKind::ASK{ turtles, previous_turtle, restore_turtle: ask.restore_turtle }));
                     }
                 }
                 CodeBlockType::PROCEDURE(_) => {}
@@ -107,83 +673,110 @@ fn execute_instruction(instruction: &ParserSymbol, svg: &mut String, procedures:
             {
                 CommandType::FORWARD =>
                 {
-                    let distance = command.call_parameter.evaluate(variables);
-                    println!("FD {}", distance);
-                    let new_x = turtles[*current_turtle].x + (turtles[*current_turtle].dir_x * distance);
-                    let new_y = turtles[*current_turtle].y + (turtles[*current_turtle].dir_y * distance);
-                    if !turtles[*current_turtle].lifted
-                    {
-                        svg.push_str(&format!("\n\t<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" style=\"stroke:{};stroke-width:1\" />", turtles[*current_turtle].x, turtles[*current_turtle].y, new_x, new_y, turtles[*current_turtle].stroke_color));
-                    }
-                    turtles[*current_turtle].x = new_x;
-                    turtles[*current_turtle].y = new_y;
+                    let distance = command.call_parameter.evaluate(variables, &state.globals)?;
+                    state.move_turtle(distance)?;
                 }
                 CommandType::BACKWARD =>
                 {
-                    let distance = command.call_parameter.evaluate(variables);
-                    println!("BK {}", distance);
-                    let new_x = turtles[*current_turtle].x - (turtles[*current_turtle].dir_x * distance);
-                    let new_y = turtles[*current_turtle].y - (turtles[*current_turtle].dir_y * distance);
-                    if !turtles[*current_turtle].lifted
-                    {
-                        svg.push_str(&format!("\n\t<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" style=\"stroke:{};stroke-width:1\" />", turtles[*current_turtle].x, turtles[*current_turtle].y, new_x, new_y, turtles[*current_turtle].stroke_color));
-                    }
-                    turtles[*current_turtle].x = new_x;
-                    turtles[*current_turtle].y = new_y;
+                    let distance = command.call_parameter.evaluate(variables, &state.globals)?;
+                    state.move_turtle(-distance)?;
                 }
                 CommandType::TURN_RIGHT =>
                 {
-                    let turn_degrees = command.call_parameter.evaluate(variables) % 360.0;
-                    println!("RT {}", turn_degrees);
-                    turtles[*current_turtle].rotate_right(turn_degrees);
+                    let turn_degrees = command.call_parameter.evaluate(variables, &state.globals)? % 360.0;
+                    state.turn(turn_degrees);
                 }
                 CommandType::TURN_LEFT =>
                 {
-                    let turn_degrees = command.call_parameter.evaluate(variables) % 360.0;
-                    println!("LT {}", turn_degrees);
-                    turtles[*current_turtle].rotate_right(360.0-turn_degrees);
+                    let turn_degrees = command.call_parameter.evaluate(variables, &state.globals)? % 360.0;
+                    state.turn(360.0-turn_degrees);
                 }
                 CommandType::PEN_UP =>
                 {
-                    turtles[*current_turtle].lifted = true;
+                    state.turtle().lifted = true;
                 }
                 CommandType::PEN_DOWN =>
                 {
-                    turtles[*current_turtle].lifted = false;
+                    state.turtle().lifted = false;
+                }
+                CommandType::PEN_PAINT | CommandType::PEN_ERASE | CommandType::PEN_REVERSE =>
+                {
+                    state.set_pen_mode(command.command_type);
                 }
                 CommandType::STOP =>
                 {
-                    println!("STOP");
-                    *stop = true;
-                    return;
+                    // Leaves the blocks the stop is in, up to and including its procedure
+                    while let Some(frame) = frames.pop()
+                    {
+                        let is_procedure = matches!(frame.kind, FrameKind::PROCEDURE{ .. });
+                        close_frame(state, frames, frame)?;
+                        if is_procedure { break; }
+                    }
                 }
                 CommandType::SET_LABEL_HEIGHT =>
                 {
-                    println!("SET_LABEL_HEIGHT");
-                    turtles[*current_turtle].label_height = command.call_parameter.evaluate(variables).round() as i32;
+                    state.turtle().label_height = command.call_parameter.evaluate(variables, &state.globals)?.round() as i32;
                 }
                 CommandType::LABEL =>
                 {
-                    println!("LABEL");
-                    let text = command.call_parameter.text_literal();
-                    let rotation_angle = (turtles[*current_turtle].dir_y.atan2(turtles[*current_turtle].dir_x) * 180.0 / PI).round();
-                    svg.push_str(&format!("\n\t<text x=\"{}\" y=\"{}\" fill=\"{}\" font-size=\"{}\" font-family=\"Arial\" transform=\"rotate({} {},{})\">{}</text>",
-                                                 turtles[*current_turtle].x, turtles[*current_turtle].y, turtles[*current_turtle].stroke_color, turtles[*current_turtle].label_height, rotation_angle, turtles[*current_turtle].x, turtles[*current_turtle].y, text));
+                    state.label(command.call_parameter.text_literal())?;
                 }
                 CommandType::SET_TURTLE =>
                 {
-                    *current_turtle = command.call_parameter.evaluate(variables).round() as usize - 1;
+                    let turtle = command.call_parameter.evaluate(variables, &state.globals)?.round() as i64;
+                    state.select_turtle(turtle)?;
                 }
                 CommandType::SET_COLOR =>
                 {
-                    let color = command.call_parameter.evaluate_setcolor();
-                    turtles[*current_turtle].stroke_color = color;
+                    let color = command.call_parameter.evaluate_color(variables, &state.globals)?;
+                    state.turtle().stroke_color = color;
+                }
+                CommandType::SET_BACKGROUND =>
+                {
+                    let color = command.call_parameter.evaluate_color(variables, &state.globals)?;
+                    state.set_background(color);
+                }
+                CommandType::CLEAN =>
+                {
+                    state.clean();
+                }
+                CommandType::CLEAR_SCREEN =>
+                {
+                    state.clear_screen();
+                }
+                CommandType::WRAP =>
+                {
+                    state.boundary_mode = BoundaryMode::WRAP;
+                }
+                CommandType::FENCE =>
+                {
+                    state.boundary_mode = BoundaryMode::FENCE;
+                }
+                CommandType::WINDOW =>
+                {
+                    state.boundary_mode = BoundaryMode::WINDOW;
+                }
+                CommandType::HIDE_TURTLE =>
+                {
+                    state.set_visible(false);
+                }
+                CommandType::SHOW_TURTLE =>
+                {
+                    state.set_visible(true);
+                }
+                CommandType::SET_SHAPE =>
+                {
+                    let shape = command.call_parameter.text_literal();
+                    state.set_shape(shape)?;
+                }
+                CommandType::WAIT =>
+                {
+                    let ticks = command.call_parameter.evaluate(variables, &state.globals)?;
+                    state.wait(ticks);
                 }
-                CommandType::CLEAR_SCREEN | CommandType::HIDE_TURTLE | 
-                CommandType::SHOW_TURTLE | CommandType::WINDOW | 
-                CommandType::WAIT => {}
                 _ => {}
             }
         }
     }
-}   
\ No newline at end of file
+    Ok(())
+}
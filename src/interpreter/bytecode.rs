@@ -0,0 +1,328 @@
+// Compiles the parsed program to flat lists of operations run by the virtual machine in vm.rs.
+// Blocks become jumps, called procedures are resolved to their index and the variables of a procedure to slots
+
+use std::collections::{ HashMap, LinkedList };
+use crate::parser::{ CodeBlock, ParserSymbol, CodeBlockType, CommandType };
+use crate::expression::{ palette_color, Expression, ExpressionSymbol, ExpressionError };
+use super::BoundaryMode;
+
+// Where the operations of an expression are in the expression code
+#[derive(Clone, Copy)]
+pub struct ExpressionRange
+{
+    pub start: u32,
+    pub end: u32
+}
+
+pub enum ExpressionOperation
+{
+    CONSTANT(f32),
+    LOCAL(u32, u32),   // slot of the procedure, and the name looked up in the globals while the slot is unset
+    GLOBAL(u32),       // name
+    ADD,
+    SUBTRACT,
+    MULTIPLY,
+    DIVIDE,
+    LESS,
+    RANDOM,            // whole number below the value on top of the stack
+    FAIL(u32)          // error
+}
+
+pub enum Color
+{
+    EMPTY,
+    PICK(Vec<Option<String>>),                                            // None for entries which are not colours
+    NAME{ name: String, slot: Option<u32>, number: ExpressionRange },   // the name itself, unless it is a variable giving a number
+    NUMBER(ExpressionRange)
+}
+
+pub struct Call
+{
+    pub procedure: Option<u32>,                     // None when the procedure is not defined
+    pub procedure_name: String,
+    pub arguments: Vec<(u32, ExpressionRange)>      // slot of the parameter and its value
+}
+
+pub enum Operation
+{
+    FORWARD(ExpressionRange),
+    BACKWARD(ExpressionRange),
+    TURN_RIGHT(ExpressionRange),
+    TURN_LEFT(ExpressionRange),
+    PEN_UP,
+    PEN_DOWN,
+    PEN_MODE(CommandType),
+    SET_LABEL_HEIGHT(ExpressionRange),
+    LABEL(u32),                    // text
+    SET_TURTLE(ExpressionRange),
+    SET_COLOR(u32),                // colour
+    SET_BACKGROUND(u32),           // colour
+    CLEAN,
+    CLEAR_SCREEN,
+    BOUNDARY(BoundaryMode),
+    SET_VISIBLE(bool),
+    SET_SHAPE(u32),                // text
+    WAIT(ExpressionRange),
+    NOTHING,                       // pick and random used as commands
+    STOP,
+    CALL(u32),
+    LOOP{ repeats: ExpressionRange, slot: u32, end: u32 },
+    NEXT_ITERATION{ slot: u32, start: u32 },
+    IF{ condition: ExpressionRange, end: u32 },
+    ASK{ turtles: u32, restore_turtle: bool, end: u32 },
+    NEXT_TURTLE{ start: u32 },
+    RETURN
+}
+
+pub struct CompiledProcedure
+{
+    pub start: u32,
+    pub slots: u32   // parameters and repcount
+}
+
+pub struct Program
+{
+    pub code: Vec<Operation>,
+    pub expression_code: Vec<ExpressionOperation>,
+    pub procedures: Vec<CompiledProcedure>,
+    pub main: Option<u32>,
+    pub names: Vec<String>,
+    pub errors: Vec<ExpressionError>,
+    pub texts: Vec<String>,
+    pub colors: Vec<Color>,
+    pub calls: Vec<Call>,
+    pub turtle_lists: Vec<Vec<ExpressionRange>>
+}
+
+struct Compiler<'a>
+{
+    program: Program,
+    procedures: Vec<&'a CodeBlock>,
+    procedure_indexes: HashMap<&'a str, u32>,
+    name_indexes: HashMap<String, u32>,
+    slots: HashMap<String, u32>,   // of the procedure being compiled
+    repcount_slot: u32
+}
+
+pub fn compile(procedures: &HashMap<String, CodeBlock>, main_procedure_name: &str) -> Program
+{
+    let procedure_indexes: HashMap<&str, u32> = procedures.keys().enumerate().map(|(index, procedure_name)| (procedure_name.as_str(), index as u32)).collect();
+    let program = Program{ code: vec!(), expression_code: vec!(), procedures: vec!(), main: procedure_indexes.get(main_procedure_name).copied(), names: vec!(),
+                           errors: vec!(), texts: vec!(), colors: vec!(), calls: vec!(), turtle_lists: vec!() };
+    let mut compiler = Compiler{ program, procedures: procedures.values().collect(), procedure_indexes, name_indexes: HashMap::new(), slots: HashMap::new(), repcount_slot: 0 };
+    for procedure in compiler.procedures.clone()
+    {
+        compiler.compile_procedure(procedure);
+    }
+    compiler.program
+}
+
+// Slots of the parameters in order. A parameter named twice has a single slot, the last value given to it wins as in a scope
+fn parameter_slots(call_parameters: &LinkedList<String>, slots: &mut HashMap<String, u32>) -> Vec<u32>
+{
+    call_parameters.iter().map(|call_parameter|
+    {
+        let next_slot = slots.len() as u32;
+        *slots.entry(call_parameter.clone()).or_insert(next_slot)
+    }).collect()
+}
+
+impl<'a> Compiler<'a>
+{
+    fn compile_procedure(&mut self, procedure: &CodeBlock)
+    {
+        self.slots.clear();
+        if let CodeBlockType::PROCEDURE(procedure) = &procedure.code_block_type
+        {
+            parameter_slots(&procedure.call_parameters, &mut self.slots);
+        }
+        let next_slot = self.slots.len() as u32;
+        self.repcount_slot = *self.slots.entry("repcount".to_string()).or_insert(next_slot);
+
+        let start = self.program.code.len() as u32;
+        self.compile_instructions(procedure);
+        self.program.code.push(Operation::RETURN);
+        self.program.procedures.push(CompiledProcedure{ start, slots: self.slots.len() as u32 });
+    }
+
+    fn name(&mut self, name: &str) -> u32
+    {
+        if let Some(index) = self.name_indexes.get(name)
+        {
+            return *index;
+        }
+        let index = self.program.names.len() as u32;
+        self.program.names.push(name.to_string());
+        self.name_indexes.insert(name.to_string(), index);
+        index
+    }
+
+    fn text(&mut self, expression: &Expression) -> u32
+    {
+        self.program.texts.push(expression.text_literal());
+        self.program.texts.len() as u32 - 1
+    }
+
+    fn jump_target(&self) -> u32
+    {
+        self.program.code.len() as u32
+    }
+
+    fn compile_instructions(&mut self, code_block: &CodeBlock)
+    {
+        for instruction in code_block.get_instructions().iter()
+        {
+            match instruction
+            {
+                ParserSymbol::PROCEDURE_CALL(procedure_call) =>
+                {
+                    let procedure = self.procedure_indexes.get(procedure_call.procedure_name.as_str()).copied();
+                    let slots = match procedure.map(|procedure| &self.procedures[procedure as usize].code_block_type)
+                    {
+                        Some(CodeBlockType::PROCEDURE(called)) => parameter_slots(&called.call_parameters, &mut HashMap::new()),
+                        _ => vec!()
+                    };
+                    let arguments = slots.into_iter().zip(procedure_call.parameter_expressions.iter()).map(|(slot, expression)| (slot, self.compile_expression(expression))).collect();
+                    self.program.calls.push(Call{ procedure, procedure_name: procedure_call.procedure_name.clone(), arguments });
+                    self.program.code.push(Operation::CALL(self.program.calls.len() as u32 - 1));
+                }
+                ParserSymbol::CODE_BLOCK(code_block) => self.compile_code_block(code_block),
+                ParserSymbol::COMMAND(command) =>
+                {
+                    let operation = match command.command_type
+                    {
+                        CommandType::FORWARD => Operation::FORWARD(self.compile_expression(&command.call_parameter)),
+                        CommandType::BACKWARD => Operation::BACKWARD(self.compile_expression(&command.call_parameter)),
+                        CommandType::TURN_RIGHT => Operation::TURN_RIGHT(self.compile_expression(&command.call_parameter)),
+                        CommandType::TURN_LEFT => Operation::TURN_LEFT(self.compile_expression(&command.call_parameter)),
+                        CommandType::PEN_UP => Operation::PEN_UP,
+                        CommandType::PEN_DOWN => Operation::PEN_DOWN,
+                        CommandType::PEN_PAINT | CommandType::PEN_ERASE | CommandType::PEN_REVERSE => Operation::PEN_MODE(command.command_type),
+                        CommandType::STOP => Operation::STOP,
+                        CommandType::SET_LABEL_HEIGHT => Operation::SET_LABEL_HEIGHT(self.compile_expression(&command.call_parameter)),
+                        CommandType::LABEL => Operation::LABEL(self.text(&command.call_parameter)),
+                        CommandType::SET_TURTLE => Operation::SET_TURTLE(self.compile_expression(&command.call_parameter)),
+                        CommandType::SET_COLOR => Operation::SET_COLOR(self.compile_color(&command.call_parameter)),
+                        CommandType::SET_BACKGROUND => Operation::SET_BACKGROUND(self.compile_color(&command.call_parameter)),
+                        CommandType::CLEAN => Operation::CLEAN,
+                        CommandType::CLEAR_SCREEN => Operation::CLEAR_SCREEN,
+                        CommandType::WRAP => Operation::BOUNDARY(BoundaryMode::WRAP),
+                        CommandType::FENCE => Operation::BOUNDARY(BoundaryMode::FENCE),
+                        CommandType::WINDOW => Operation::BOUNDARY(BoundaryMode::WINDOW),
+                        CommandType::HIDE_TURTLE => Operation::SET_VISIBLE(false),
+                        CommandType::SHOW_TURTLE => Operation::SET_VISIBLE(true),
+                        CommandType::SET_SHAPE => Operation::SET_SHAPE(self.text(&command.call_parameter)),
+                        CommandType::WAIT => Operation::WAIT(self.compile_expression(&command.call_parameter)),
+                        CommandType::PICK | CommandType::RANDOM => Operation::NOTHING
+                    };
+                    self.program.code.push(operation);
+                }
+            }
+        }
+    }
+
+    fn compile_code_block(&mut self, code_block: &CodeBlock)
+    {
+        match &code_block.code_block_type
+        {
+            CodeBlockType::LOOP(my_loop) =>
+            {
+                let repeats = self.compile_expression(&my_loop.repeats);
+                let slot = self.repcount_slot;
+                let loop_start = self.jump_target();
+                self.program.code.push(Operation::LOOP{ repeats, slot, end: 0 });
+                self.compile_instructions(code_block);
+                self.program.code.push(Operation::NEXT_ITERATION{ slot, start: loop_start + 1 });
+                let loop_end = self.jump_target();
+                if let Operation::LOOP{ end, .. } = &mut self.program.code[loop_start as usize] { *end = loop_end; }
+            }
+            CodeBlockType::IF(my_if) =>
+            {
+                let condition = self.compile_expression(&my_if.condition);
+                let if_start = self.jump_target();
+                self.program.code.push(Operation::IF{ condition, end: 0 });
+                self.compile_instructions(code_block);
+                let if_end = self.jump_target();
+                if let Operation::IF{ end, .. } = &mut self.program.code[if_start as usize] { *end = if_end; }
+            }
+            CodeBlockType::ASK(ask) =>
+            {
+                let turtles = ask.turtles.iter().map(|turtle| self.compile_expression(turtle)).collect();
+                self.program.turtle_lists.push(turtles);
+                let ask_start = self.jump_target();
+                self.program.code.push(Operation::ASK{ turtles: self.program.turtle_lists.len() as u32 - 1, restore_turtle: ask.restore_turtle, end: 0 });
+                self.compile_instructions(code_block);
+                self.program.code.push(Operation::NEXT_TURTLE{ start: ask_start + 1 });
+                let ask_end = self.jump_target();
+                if let Operation::ASK{ end, .. } = &mut self.program.code[ask_start as usize] { *end = ask_end; }
+            }
+            // Procedures are compiled on their own, a definition found among instructions does nothing
+            CodeBlockType::PROCEDURE(_) => self.program.code.push(Operation::NOTHING)
+        }
+    }
+
+    fn compile_variable(&mut self, variable: &str) -> ExpressionOperation
+    {
+        let name = self.name(variable);
+        match self.slots.get(variable)
+        {
+            Some(slot) => ExpressionOperation::LOCAL(*slot, name),
+            None => ExpressionOperation::GLOBAL(name)
+        }
+    }
+
+    fn compile_expression(&mut self, expression: &Expression) -> ExpressionRange
+    {
+        let start = self.program.expression_code.len() as u32;
+        for symbol in expression.symbols().iter()
+        {
+            let operation = match symbol
+            {
+                ExpressionSymbol::CONSTANT(constant) => ExpressionOperation::CONSTANT(*constant),
+                ExpressionSymbol::VARIABLE(variable) => self.compile_variable(variable),
+                ExpressionSymbol::INVALID_NUMBER(number) =>
+                {
+                    self.program.errors.push(ExpressionError::INVALID_NUMBER(number.clone()));
+                    ExpressionOperation::FAIL(self.program.errors.len() as u32 - 1)
+                }
+                ExpressionSymbol::OPERATOR(operator) => match operator.as_str()
+                {
+                    "+" => ExpressionOperation::ADD,
+                    "-" => ExpressionOperation::SUBTRACT,
+                    "*" => ExpressionOperation::MULTIPLY,
+                    "/" => ExpressionOperation::DIVIDE,
+                    _ => ExpressionOperation::LESS
+                },
+                ExpressionSymbol::FUNCTION_COMMAND(function_command) if function_command == "random" => ExpressionOperation::RANDOM,
+                _ => continue
+            };
+            self.program.expression_code.push(operation);
+        }
+        ExpressionRange{ start, end: self.program.expression_code.len() as u32 }
+    }
+
+    fn compile_color(&mut self, expression: &Expression) -> u32
+    {
+        let color = match expression.symbols().front()
+        {
+            None => Color::EMPTY,
+            Some(ExpressionSymbol::FUNCTION_COMMAND(function_command)) if function_command == "pick" =>
+            {
+                Color::PICK(expression.symbols().iter().skip(1).map(|symbol| match symbol
+                {
+                    ExpressionSymbol::VARIABLE(color) => Some(color.clone()),
+                    ExpressionSymbol::CONSTANT(color_idx) => Some(palette_color(*color_idx)),
+                    _ => None
+                }).collect())
+            }
+            Some(ExpressionSymbol::VARIABLE(name)) =>
+            {
+                let slot = self.slots.get(name).copied();
+                Color::NAME{ name: name.clone(), slot, number: self.compile_expression(expression) }
+            }
+            Some(_) => Color::NUMBER(self.compile_expression(expression))
+        };
+        self.program.colors.push(color);
+        self.program.colors.len() as u32 - 1
+    }
+}
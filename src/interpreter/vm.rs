@@ -0,0 +1,355 @@
+// Runs a program compiled by bytecode.rs. It gives the same drawing, errors and random numbers as the tree-walker,
+// and counts the same instructions towards the limits
+
+use std::collections::HashMap;
+use crate::expression::{ palette_color, random_index, random_number, ExpressionError };
+use super::bytecode::{ Program, Operation, ExpressionOperation, ExpressionRange, Color };
+use super::{ InterpreterState, RuntimeError };
+
+// A block being run, its operations are between its start and end operations
+enum Block
+{
+    LOOP{ iteration: i32, repeats: i32 },
+    ASK{ turtles: Vec<i64>, next: usize, previous_turtle: usize, restore_turtle: bool }
+}
+
+// Where a procedure returns to, with the locals and blocks of its caller
+struct Frame
+{
+    return_pc: usize,
+    locals_base: usize,
+    blocks_base: usize
+}
+
+struct Machine<'p>
+{
+    program: &'p Program,
+    pc: usize,
+    locals_base: usize,
+    blocks_base: usize,
+    locals: Vec<Option<f32>>,   // slots of the procedures running, None until set
+    blocks: Vec<Block>,
+    frames: Vec<Frame>,
+    stack: Vec<f32>,            // of the expression being evaluated
+    arguments: Vec<f32>
+}
+
+pub fn run(program: &Program, state: &mut InterpreterState) -> Result<(), RuntimeError>
+{
+    let Some(main) = program.main else { return Ok(()); };
+    let main = &program.procedures[main as usize];
+    let mut machine = Machine{ program, pc: main.start as usize, locals_base: 0, blocks_base: 0, locals: vec![None; main.slots as usize],
+                               blocks: vec!(), frames: vec!(), stack: vec!(), arguments: vec!() };
+    machine.run(state)
+}
+
+impl<'p> Machine<'p>
+{
+    fn global(&self, name: u32, globals: &HashMap<String, f32>) -> Result<f32, ExpressionError>
+    {
+        let name = &self.program.names[name as usize];
+        globals.get(name).copied().ok_or_else(|| ExpressionError::UNKNOWN_VARIABLE(name.clone()))
+    }
+
+    fn set_local(&mut self, slot: u32, value: Option<f32>)
+    {
+        self.locals[self.locals_base + slot as usize] = value;
+    }
+
+    fn evaluate(&mut self, expression: ExpressionRange, globals: &HashMap<String, f32>) -> Result<f32, ExpressionError>
+    {
+        let program = self.program;
+        self.stack.clear();
+        for operation in &program.expression_code[expression.start as usize..expression.end as usize]
+        {
+            let value = match *operation
+            {
+                ExpressionOperation::CONSTANT(constant) => constant,
+                ExpressionOperation::LOCAL(slot, name) => match self.locals[self.locals_base + slot as usize]
+                {
+                    Some(value) => value,
+                    None => self.global(name, globals)?
+                },
+                ExpressionOperation::GLOBAL(name) => self.global(name, globals)?,
+                ExpressionOperation::RANDOM => random_number(self.stack.pop().ok_or(ExpressionError::EMPTY)?)?,
+                ExpressionOperation::FAIL(error) => return Err(program.errors[error as usize].clone()),
+                _ =>
+                {
+                    let (Some(y), Some(x)) = (self.stack.pop(), self.stack.pop())
+                    else
+                    {
+                        let operator = match operation { ExpressionOperation::ADD => "+", ExpressionOperation::SUBTRACT => "-", ExpressionOperation::MULTIPLY => "*", ExpressionOperation::DIVIDE => "/", _ => "<" };
+                        return Err(ExpressionError::MISSING_OPERAND(operator.to_string()));
+                    };
+                    match operation
+                    {
+                        ExpressionOperation::ADD => x + y,
+                        ExpressionOperation::SUBTRACT => x - y,
+                        ExpressionOperation::MULTIPLY => x * y,
+                        ExpressionOperation::DIVIDE => x / y,
+                        _ => if x < y { 1.0 } else { 0.0 }
+                    }
+                }
+            };
+            self.stack.push(value);
+        }
+        self.stack.pop().ok_or(ExpressionError::EMPTY)
+    }
+
+    fn evaluate_color(&mut self, color: u32, globals: &HashMap<String, f32>) -> Result<String, ExpressionError>
+    {
+        match &self.program.colors[color as usize]
+        {
+            Color::EMPTY => Err(ExpressionError::EMPTY),
+            Color::PICK(colors) =>
+            {
+                if colors.is_empty()
+                {
+                    return Err(ExpressionError::EMPTY_PICK);
+                }
+                colors[random_index(colors.len())].clone().ok_or(ExpressionError::INVALID_COLOR)
+            }
+            Color::NAME{ name, slot, number } =>
+            {
+                let is_variable = slot.is_some_and(|slot| self.locals[self.locals_base + slot as usize].is_some()) || globals.contains_key(name);
+                if !is_variable
+                {
+                    return Ok(name.clone());
+                }
+                Ok(palette_color(self.evaluate(*number, globals)?))
+            }
+            Color::NUMBER(number) => Ok(palette_color(self.evaluate(*number, globals)?))
+        }
+    }
+
+    // Cleans up after a block left for good, when its end is reached or a stop leaves it early
+    fn close_block(block: Block, state: &mut InterpreterState) -> Result<(), RuntimeError>
+    {
+        match block
+        {
+            Block::LOOP{ .. } => {}
+            Block::ASK{ previous_turtle, restore_turtle, .. } =>
+            {
+                // tell leaves the last turtle of the list as the current one
+                if restore_turtle
+                {
+                    state.select_turtle(previous_turtle as i64)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn call(&mut self, call: u32, state: &mut InterpreterState) -> Result<(), RuntimeError>
+    {
+        let program = self.program;
+        let call = &program.calls[call as usize];
+        // A procedure whose definition is cut short by another one is called without having been stored
+        let procedure = call.procedure.ok_or_else(|| RuntimeError::UNKNOWN_PROCEDURE(call.procedure_name.clone()))?;
+        self.arguments.clear();
+        for (_, argument) in call.arguments.iter()
+        {
+            let value = self.evaluate(*argument, &state.globals)?;
+            self.arguments.push(value);
+        }
+
+        // Nothing is left to run after a call followed by the end of its procedure, the called procedure takes its place
+        if let Operation::RETURN = program.code[self.pc]
+        {
+            self.locals.truncate(self.locals_base);
+        }
+        else
+        {
+            // The main program counts as a procedure running
+            if let Some(max_depth) = state.max_depth.filter(|max_depth| self.frames.len() + 2 > *max_depth)
+            {
+                return Err(RuntimeError::DEPTH_LIMIT(max_depth));
+            }
+            self.frames.push(Frame{ return_pc: self.pc, locals_base: self.locals_base, blocks_base: self.blocks_base });
+            self.locals_base = self.locals.len();
+            self.blocks_base = self.blocks.len();
+        }
+
+        let procedure = &program.procedures[procedure as usize];
+        self.locals.resize(self.locals_base + procedure.slots as usize, None);
+        for ((slot, _), value) in call.arguments.iter().zip(self.arguments.iter())
+        {
+            self.locals[self.locals_base + *slot as usize] = Some(*value);
+        }
+        self.pc = procedure.start as usize;
+        Ok(())
+    }
+
+    // Returns to the caller, false when the main program is over
+    fn return_from_procedure(&mut self) -> bool
+    {
+        self.locals.truncate(self.locals_base);
+        match self.frames.pop()
+        {
+            Some(frame) =>
+            {
+                self.pc = frame.return_pc;
+                self.locals_base = frame.locals_base;
+                self.blocks_base = frame.blocks_base;
+                true
+            }
+            None => false
+        }
+    }
+
+    fn run(&mut self, state: &mut InterpreterState) -> Result<(), RuntimeError>
+    {
+        let program = self.program;
+        loop
+        {
+            let operation = &program.code[self.pc];
+            self.pc += 1;
+            // Every operation but the ends of blocks and procedures is an instruction of the parsed program
+            if !matches!(operation, Operation::NEXT_ITERATION{ .. } | Operation::NEXT_TURTLE{ .. } | Operation::RETURN)
+            {
+                state.step()?;
+            }
+            match *operation
+            {
+                Operation::FORWARD(distance) =>
+                {
+                    let distance = self.evaluate(distance, &state.globals)?;
+                    state.move_turtle(distance)?;
+                }
+                Operation::BACKWARD(distance) =>
+                {
+                    let distance = self.evaluate(distance, &state.globals)?;
+                    state.move_turtle(-distance)?;
+                }
+                Operation::TURN_RIGHT(turn_degrees) =>
+                {
+                    let turn_degrees = self.evaluate(turn_degrees, &state.globals)? % 360.0;
+                    state.turn(turn_degrees);
+                }
+                Operation::TURN_LEFT(turn_degrees) =>
+                {
+                    let turn_degrees = self.evaluate(turn_degrees, &state.globals)? % 360.0;
+                    state.turn(360.0-turn_degrees);
+                }
+                Operation::PEN_UP => state.turtle().lifted = true,
+                Operation::PEN_DOWN => state.turtle().lifted = false,
+                Operation::PEN_MODE(command_type) => state.set_pen_mode(command_type),
+                Operation::SET_LABEL_HEIGHT(label_height) =>
+                {
+                    state.turtle().label_height = self.evaluate(label_height, &state.globals)?.round() as i32;
+                }
+                Operation::LABEL(text) => state.label(program.texts[text as usize].clone())?,
+                Operation::SET_TURTLE(turtle) =>
+                {
+                    let turtle = self.evaluate(turtle, &state.globals)?.round() as i64;
+                    state.select_turtle(turtle)?;
+                }
+                Operation::SET_COLOR(color) =>
+                {
+                    let color = self.evaluate_color(color, &state.globals)?;
+                    state.turtle().stroke_color = color;
+                }
+                Operation::SET_BACKGROUND(color) =>
+                {
+                    let color = self.evaluate_color(color, &state.globals)?;
+                    state.set_background(color);
+                }
+                Operation::CLEAN => state.clean(),
+                Operation::CLEAR_SCREEN => state.clear_screen(),
+                Operation::BOUNDARY(boundary_mode) => state.boundary_mode = boundary_mode,
+                Operation::SET_VISIBLE(visible) => state.set_visible(visible),
+                Operation::SET_SHAPE(shape) => state.set_shape(program.texts[shape as usize].clone())?,
+                Operation::WAIT(ticks) =>
+                {
+                    let ticks = self.evaluate(ticks, &state.globals)?;
+                    state.wait(ticks);
+                }
+                Operation::NOTHING => {}
+                Operation::STOP =>
+                {
+                    // Leaves the blocks the stop is in, up to and including its procedure
+                    while self.blocks.len() > self.blocks_base
+                    {
+                        let block = self.blocks.pop().unwrap();
+                        Self::close_block(block, state)?;
+                    }
+                    if !self.return_from_procedure() { return Ok(()); }
+                }
+                Operation::CALL(call) => self.call(call, state)?,
+                Operation::LOOP{ repeats, slot, end } =>
+                {
+                    let repeats = self.evaluate(repeats, &state.globals)?.round() as i32;
+                    if repeats > 0
+                    {
+                        state.step()?;
+                        self.set_local(slot, Some(0.0));
+                        self.blocks.push(Block::LOOP{ iteration: 0, repeats });
+                    }
+                    else
+                    {
+                        self.set_local(slot, None);
+                        self.pc = end as usize;
+                    }
+                }
+                Operation::NEXT_ITERATION{ slot, start } =>
+                {
+                    let Some(Block::LOOP{ iteration, repeats }) = self.blocks.last_mut() else { unreachable!() };
+                    if *iteration + 1 < *repeats
+                    {
+                        *iteration += 1;
+                        let iteration = *iteration;
+                        state.step()?;
+                        self.set_local(slot, Some(iteration as f32));
+                        self.pc = start as usize;
+                    }
+                    else
+                    {
+                        self.blocks.pop();
+                        self.set_local(slot, None);
+                    }
+                }
+                Operation::IF{ condition, end } =>
+                {
+                    if self.evaluate(condition, &state.globals)? == 0.0
+                    {
+                        self.pc = end as usize;
+                    }
+                }
+                Operation::ASK{ turtles, restore_turtle, end } =>
+                {
+                    let turtles = program.turtle_lists[turtles as usize].iter().map(|turtle| Ok(self.evaluate(*turtle, &state.globals)?.round() as i64)).collect::<Result<Vec<i64>, RuntimeError>>()?;
+                    let previous_turtle = state.current_turtle;
+                    match turtles.first()
+                    {
+                        Some(turtle) =>
+                        {
+                            state.select_turtle(*turtle)?;
+                            self.blocks.push(Block::ASK{ turtles, next: 1, previous_turtle, restore_turtle });
+                        }
+                        None => self.pc = end as usize
+                    }
+                }
+                Operation::NEXT_TURTLE{ start } =>
+                {
+                    let Some(Block::ASK{ turtles, next, .. }) = self.blocks.last_mut() else { unreachable!() };
+                    if *next < turtles.len()
+                    {
+                        let turtle = turtles[*next];
+                        *next += 1;
+                        self.pc = start as usize;
+                        state.select_turtle(turtle)?;
+                    }
+                    else
+                    {
+                        let block = self.blocks.pop().unwrap();
+                        Self::close_block(block, state)?;
+                    }
+                }
+                Operation::RETURN =>
+                {
+                    if !self.return_from_procedure() { return Ok(()); }
+                }
+            }
+        }
+    }
+}
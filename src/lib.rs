@@ -0,0 +1,11 @@
+#![allow(non_camel_case_types, non_snake_case, clippy::upper_case_acronyms)]
+
+pub mod expression;
+pub mod parser;
+pub mod interpreter;
+pub mod logo_manager;
+pub mod drawing;
+pub mod renderer;
+pub mod cli;
+pub mod repl;
+pub mod server;
@@ -1,41 +1,235 @@
 use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{ Duration, SystemTime };
 
 use crate::parser::parse_logo_code;
-use crate::parser::CodeBlock;
-use crate::interpreter::execute_logo_program;
-use std::collections::HashMap;
+use crate::interpreter::{ execute_logo_program, InterpreterOptions };
+use crate::renderer::Renderer;
+use crate::renderer::svg::SvgRenderer;
+use crate::renderer::gcode::GcodeRenderer;
+use crate::renderer::hpgl::HpglRenderer;
+use crate::renderer::pdf::PdfRenderer;
+use crate::renderer::eps::EpsRenderer;
+use crate::renderer::vector::PageSetup;
+use crate::renderer::preview::PreviewRenderer;
+use crate::renderer::html::HtmlRenderer;
+use crate::renderer::json::{ JsonRenderer, JsonLinesRenderer };
+#[cfg(feature = "png")]
+use crate::renderer::png::PngRenderer;
+#[cfg(feature = "gif")]
+use crate::renderer::gif::GifRenderer;
 
-fn read_code_from_file(file_path: &str) -> String
+pub const WATCH_INTERVAL: Duration = Duration::from_millis(250);  // how often the inputs are checked for changes
+
+#[derive(Default)]
+pub struct Options
 {
-    fs::read_to_string(file_path)
-       .expect(format!("Unable to read the file: {file_path}").as_str())
+    pub interpreter: InterpreterOptions,
+    pub svg: SvgRenderer,
+    pub gcode: GcodeRenderer,
+    pub hpgl: HpglRenderer,
+    pub pdf: PdfRenderer,
+    pub eps: EpsRenderer,
+    pub html: HtmlRenderer,
+    pub json: JsonRenderer,
+    pub json_lines: JsonLinesRenderer,
+    pub preview: Option<PreviewRenderer>,  // print the drawings to the terminal instead of saving them
+    #[cfg(feature = "png")]
+    pub png: PngRenderer,
+    #[cfg(feature = "gif")]
+    pub gif: GifRenderer
 }
 
-fn save_to_svg_file(file_path: &str, code: String)
+impl Options
 {
-    fs::write(file_path, code).expect(format!("Unable to write to file: {file_path}", ).as_str())
+    // Crops every output format to the drawing with the margin around it
+    pub fn fit_to_content(&mut self, margin: f32)
+    {
+        (self.svg.fit_to_content, self.svg.margin) = (true, margin);
+        (self.gcode.fit_to_content, self.gcode.margin) = (true, margin);
+        (self.hpgl.fit_to_content, self.hpgl.margin) = (true, margin);
+        (self.pdf.fit_to_content, self.pdf.margin) = (true, margin);
+        (self.eps.fit_to_content, self.eps.margin) = (true, margin);
+        (self.html.fit_to_content, self.html.margin) = (true, margin);
+        if let Some(preview) = &mut self.preview
+        {
+            preview.margin = margin;
+        }
+        #[cfg(feature = "png")]
+        { (self.png.fit_to_content, self.png.margin) = (true, margin); }
+        #[cfg(feature = "gif")]
+        { (self.gif.fit_to_content, self.gif.margin) = (true, margin); }
+    }
+
+    pub fn set_page_setup(&mut self, page: PageSetup)
+    {
+        self.pdf.page = page;
+        self.eps.page = page;
+    }
+
+    pub fn set_plot_scale(&mut self, millimetres_per_unit: f32)
+    {
+        self.gcode.millimetres_per_unit = millimetres_per_unit;
+        self.hpgl.millimetres_per_unit = millimetres_per_unit;
+    }
+
+    pub fn set_travel_optimization(&mut self, optimize_travel: bool)
+    {
+        self.gcode.optimize_travel = optimize_travel;
+        self.hpgl.optimize_travel = optimize_travel;
+    }
 }
 
-pub fn parse_and_execute(logo_file_paths: &[&str], svg_file_paths: &[&str])
+pub const REPORTERS: [&str; 3] = ["repcount", "who", "turtles"];  // read like variables, written without the colon
+
+// Source as the parser expects it, with the reporters turned into variables wherever they are a whole word
+pub fn prepare_code(code: &str) -> String
 {
-    let mut logo_codes: Vec<String> = vec!();
-    for logo_file_path in logo_file_paths
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let mut prepared = String::with_capacity(code.len() + 1);
+    let mut rest = code;
+    while let Some(start) = rest.find(is_word_char)
     {
-        let mut code = read_code_from_file(logo_file_path).replace("repcount", ":repcount");
-        code.push(' ');
-        logo_codes.push(code);
+        let end = rest[start..].find(|c: char| !is_word_char(c)).map_or(rest.len(), |length| start + length);
+        prepared.push_str(&rest[..start]);
+        if REPORTERS.contains(&&rest[start..end]) && !prepared.ends_with([':', '"'])
+        {
+            prepared.push(':');
+        }
+        prepared.push_str(&rest[start..end]);
+        rest = &rest[end..];
     }
+    prepared.push_str(rest);
+    prepared.push(' ');
+    prepared
+}
 
-    let mut logo_parsed_codes: Vec<HashMap<String, CodeBlock>> = vec!();
-    for logo_code in logo_codes
+// Output format is chosen by the extension of the file
+pub fn renderer_for_file<'a>(file_path: &str, options: &'a Options) -> Option<&'a dyn Renderer>
+{
+    let extension = Path::new(file_path).extension().map(|extension| extension.to_string_lossy().to_lowercase());
+    match extension.as_deref()
     {
-        let parsed_code = parse_logo_code(logo_code.as_str());
-        logo_parsed_codes.push(parsed_code);
+        Some("svg") => Some(&options.svg),
+        Some("gcode") | Some("nc") | Some("ngc") => Some(&options.gcode),
+        Some("hpgl") | Some("plt") => Some(&options.hpgl),
+        Some("pdf") => Some(&options.pdf),
+        Some("eps") => Some(&options.eps),
+        Some("html") | Some("htm") => Some(&options.html),
+        Some("json") => Some(&options.json),
+        Some("jsonl") => Some(&options.json_lines),
+        #[cfg(feature = "png")]
+        Some("png") => Some(&options.png),
+        #[cfg(feature = "gif")]
+        Some("gif") => Some(&options.gif),
+        _ => None
     }
+}
 
-    for logo_parsed_code_pair in logo_parsed_codes.into_iter().zip(svg_file_paths.into_iter())
+// Returns how many of the programs could not be rendered, their errors are printed
+pub fn parse_and_execute(logo_file_paths: &[&str], output_file_paths: &[&str], options: &Options) -> usize
+{
+    let mut errors = 0;
+    for (logo_file_path, output_file_path) in logo_file_paths.iter().zip(output_file_paths)
     {
-        let svg_code = execute_logo_program(logo_parsed_code_pair.0);
-        save_to_svg_file(logo_parsed_code_pair.1, svg_code);
+        let code = match fs::read_to_string(logo_file_path)
+        {
+            Ok(code) => code,
+            Err(error) =>
+            {
+                eprintln!("Unable to read the file {logo_file_path}: {error}");
+                errors += 1;
+                continue;
+            }
+        };
+        let procedures = parse_logo_code(&prepare_code(&code));
+
+        if let Some(preview) = &options.preview
+        {
+            match execute_logo_program(procedures, &options.interpreter)
+            {
+                Ok(drawing) => print!("{}", String::from_utf8_lossy(&preview.render(&drawing))),
+                Err(error) =>
+                {
+                    eprintln!("Runtime error, no preview of {output_file_path}: {error}");
+                    errors += 1;
+                }
+            }
+            continue;
+        }
+        let Some(renderer) = renderer_for_file(output_file_path, options)
+        else
+        {
+            eprintln!("Unsupported output format, {output_file_path} not saved");
+            errors += 1;
+            continue;
+        };
+        match execute_logo_program(procedures, &options.interpreter)
+        {
+            Ok(drawing) =>
+            {
+                if let Err(error) = fs::write(output_file_path, renderer.render(&drawing))
+                {
+                    eprintln!("Unable to write to file {output_file_path}: {error}");
+                    errors += 1;
+                }
+            }
+            Err(error) =>
+            {
+                eprintln!("Runtime error, {output_file_path} not saved: {error}");
+                errors += 1;
+            }
+        }
     }
-}
\ No newline at end of file
+    errors
+}
+
+fn modification_time(file_path: &str) -> Option<SystemTime>
+{
+    fs::metadata(file_path).and_then(|metadata| metadata.modified()).ok()
+}
+
+pub fn modification_times(file_paths: &[&str]) -> Vec<Option<SystemTime>>
+{
+    file_paths.iter().map(|file_path| modification_time(file_path)).collect()
+}
+
+// Indexes of the files modified since the times given, which are updated. A file being saved may be missing
+// for a moment, it is checked again later
+pub fn changed_files(file_paths: &[&str], modification_times: &mut [Option<SystemTime>]) -> Vec<usize>
+{
+    let mut changed = vec!();
+    for (index, file_path) in file_paths.iter().enumerate()
+    {
+        let modification_time = modification_time(file_path);
+        if modification_time.is_none() || modification_time == modification_times[index] { continue; }
+        modification_times[index] = modification_time;
+        changed.push(index);
+    }
+    changed
+}
+
+// Renders the inputs, then again each one that changes on disk until interrupted. Outputs are only written
+// when their input runs without errors, so a mistake in the program leaves the last good drawing in place
+pub fn watch(logo_file_paths: &[&str], output_file_paths: &[&str], options: &Options) -> !
+{
+    let render = |logo_file_path: &str, output_file_path: &str| { parse_and_execute(&[logo_file_path], &[output_file_path], options); };
+
+    let mut modification_times = modification_times(logo_file_paths);
+    for (logo_file_path, output_file_path) in logo_file_paths.iter().zip(output_file_paths)
+    {
+        render(logo_file_path, output_file_path);
+    }
+    eprintln!("Watching {} files for changes, press Ctrl+C to stop", logo_file_paths.len());
+
+    loop
+    {
+        thread::sleep(WATCH_INTERVAL);
+        for index in changed_files(logo_file_paths, &mut modification_times)
+        {
+            eprintln!("{} changed", logo_file_paths[index]);
+            render(logo_file_paths[index], output_file_paths[index]);
+        }
+    }
+}
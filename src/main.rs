@@ -1,10 +1,68 @@
-mod expression;
-mod parser;
-mod interpreter;
-mod logo_manager;
+use std::path::Path;
+use std::process::ExitCode;
 
-fn main() 
+use logo::{ cli, logo_manager, repl, server };
+use logo::cli::Command;
+
+// Failure when any of the programs could not be rendered
+fn exit_code(errors: usize) -> ExitCode
+{
+    if errors == 0 { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+}
+
+fn main() -> ExitCode
 {
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+    let arguments = match cli::parse_arguments(&arguments)
+    {
+        Ok(Command::RENDER(arguments)) => arguments,
+        Ok(Command::REPL(options)) =>
+        {
+            return match repl::run(&options)
+            {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(message) =>
+                {
+                    eprintln!("{message}");
+                    ExitCode::FAILURE
+                }
+            };
+        }
+        Ok(Command::SERVE(arguments)) =>
+        {
+            return match server::serve(Path::new(&arguments.directory), arguments.port, arguments.options)
+            {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(error) =>
+                {
+                    eprintln!("Unable to serve {}: {error}", arguments.directory);
+                    ExitCode::FAILURE
+                }
+            };
+        }
+        Ok(Command::HELP) =>
+        {
+            println!("{}", cli::USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(message) =>
+        {
+            eprintln!("{message}\n\n{}", cli::USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if !arguments.logo_file_paths.is_empty()
+    {
+        let logo_file_paths: Vec<&str> = arguments.logo_file_paths.iter().map(|path| path.as_str()).collect();
+        let output_file_paths: Vec<&str> = arguments.output_file_paths.iter().map(|path| path.as_str()).collect();
+        if arguments.watch
+        {
+            logo_manager::watch(&logo_file_paths, &output_file_paths, &arguments.options);
+        }
+        return exit_code(logo_manager::parse_and_execute(&logo_file_paths, &output_file_paths, &arguments.options));
+    }
+
     let logo_file_paths = ["resources/star.logo",
 						   "resources/colored_squares.logo",
 						   "resources/logo_spiral.logo",
@@ -23,5 +81,5 @@ fn main()
                            "svg/flower.svg",
                            "svg/rotating_circle.svg",
                            "svg/sun.svg"];
-    logo_manager::parse_and_execute(&logo_file_paths, &svg_file_paths);
+    exit_code(logo_manager::parse_and_execute(&logo_file_paths, &svg_file_paths, &arguments.options))
 }
\ No newline at end of file
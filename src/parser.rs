@@ -13,6 +13,8 @@ lazy_static!
         set.insert("repeat");
         set.insert("if");
         set.insert("end");
+        set.insert("ask");
+        set.insert("tell");
         set
     };
 
@@ -33,6 +35,12 @@ lazy_static!
         set.insert("pu", CommandType::PEN_UP);
         set.insert("pendown", CommandType::PEN_DOWN);
         set.insert("pd", CommandType::PEN_DOWN);
+        set.insert("penpaint", CommandType::PEN_PAINT);
+        set.insert("ppt", CommandType::PEN_PAINT);
+        set.insert("penerase", CommandType::PEN_ERASE);
+        set.insert("pe", CommandType::PEN_ERASE);
+        set.insert("penreverse", CommandType::PEN_REVERSE);
+        set.insert("px", CommandType::PEN_REVERSE);
         set.insert("hideturtle", CommandType::HIDE_TURTLE);
         set.insert("ht", CommandType::HIDE_TURTLE);
         set.insert("showturtle", CommandType::SHOW_TURTLE);
@@ -45,7 +53,15 @@ lazy_static!
         set.insert("label", CommandType::LABEL);
         set.insert("setlabelheight", CommandType::SET_LABEL_HEIGHT);
         set.insert("setturtle", CommandType::SET_TURTLE);
+        set.insert("setshape", CommandType::SET_SHAPE);
         set.insert("window", CommandType::WINDOW);
+        set.insert("wrap", CommandType::WRAP);
+        set.insert("fence", CommandType::FENCE);
+        set.insert("clean", CommandType::CLEAN);
+        set.insert("setbackground", CommandType::SET_BACKGROUND);
+        set.insert("setbg", CommandType::SET_BACKGROUND);
+        set.insert("setscreencolor", CommandType::SET_BACKGROUND);
+        set.insert("setsc", CommandType::SET_BACKGROUND);
         set
     };
 
@@ -58,12 +74,21 @@ lazy_static!
         set.insert("pu", CommandType::PEN_UP);
         set.insert("pendown", CommandType::PEN_DOWN);
         set.insert("pd", CommandType::PEN_DOWN);
+        set.insert("penpaint", CommandType::PEN_PAINT);
+        set.insert("ppt", CommandType::PEN_PAINT);
+        set.insert("penerase", CommandType::PEN_ERASE);
+        set.insert("pe", CommandType::PEN_ERASE);
+        set.insert("penreverse", CommandType::PEN_REVERSE);
+        set.insert("px", CommandType::PEN_REVERSE);
         set.insert("hideturtle", CommandType::HIDE_TURTLE);
         set.insert("ht", CommandType::HIDE_TURTLE);
         set.insert("showturtle", CommandType::SHOW_TURTLE);
         set.insert("st", CommandType::SHOW_TURTLE);
         set.insert("stop", CommandType::STOP);
         set.insert("window", CommandType::WINDOW);
+        set.insert("wrap", CommandType::WRAP);
+        set.insert("fence", CommandType::FENCE);
+        set.insert("clean", CommandType::CLEAN);
         set
     };
 }
@@ -78,6 +103,9 @@ pub enum CommandType
     CLEAR_SCREEN,
     PEN_UP,
     PEN_DOWN,
+    PEN_PAINT,
+    PEN_ERASE,
+    PEN_REVERSE,
     SET_COLOR,
     PICK,
     RANDOM,
@@ -88,14 +116,20 @@ pub enum CommandType
     LABEL,
     SET_LABEL_HEIGHT,
     SET_TURTLE,
-    WINDOW
+    SET_SHAPE,
+    WINDOW,
+    WRAP,
+    FENCE,
+    CLEAN,
+    SET_BACKGROUND
 }
 
 pub enum CodeBlockType
 {
     PROCEDURE(Procedure),
     LOOP(Loop),
-    IF(If)
+    IF(If),
+    ASK(Ask)
 }
 
 pub enum ParserSymbol 
@@ -148,11 +182,6 @@ impl CodeBlock
         self.instructions.push_back(instruction); 
     }
 
-    pub fn last_instruction(&mut self) -> &mut ParserSymbol
-    {
-        self.instructions.back_mut().unwrap()
-    }
-
     pub fn get_instructions(&self) -> &LinkedList<ParserSymbol>
     {
         &self.instructions
@@ -195,6 +224,19 @@ impl If
     }
 }
 
+pub struct Ask
+{
+    pub turtles: Vec<Expression>,
+    pub restore_turtle: bool
+}
+impl Ask
+{
+    fn new(turtles: Vec<Expression>, restore_turtle: bool) -> Self
+    {
+        Self { turtles, restore_turtle }
+    }
+}
+
 enum ParserState
 {
     READING_SYMBOL,
@@ -211,10 +253,9 @@ pub fn parse_logo_code(code: &str) -> HashMap<String, CodeBlock>
     let MAIN_PROCEDURE_NAME = "_".to_string();
     let mut procedures: HashMap<String, CodeBlock> = HashMap::new();
     let mut procedures_info: HashMap<String, i32> = HashMap::new();
-    //procedures.insert(MAIN_PROCEDURE_NAME.clone(), CodeBlock::new(CodeBlockType::PROCEDURE(Procedure::new())));
     let mut current_procedure_name = MAIN_PROCEDURE_NAME.clone();
-    let mut current_procedure = CodeBlock::new(CodeBlockType::PROCEDURE(Procedure::new()));
-    let mut current_code_block: &mut CodeBlock = &mut current_procedure;
+    // Bottom of the stack is the main procedure, a procedure definition and each open block are pushed on top of it
+    let mut code_block_stack: Vec<CodeBlock> = vec![CodeBlock::new(CodeBlockType::PROCEDURE(Procedure::new()))];
 
     while let Some(ch) = code_iterator.next()
     {
@@ -231,64 +272,54 @@ pub fn parse_logo_code(code: &str) -> HashMap<String, CodeBlock>
                             "to" => 
                             { 
                                 state = ParserState::READING_PROCEDURE_NAME;
+                                code_block_stack.push(CodeBlock::new(CodeBlockType::PROCEDURE(Procedure::new())));
                             }
                             "end" => 
                             { 
-                                procedures.insert(current_procedure_name.clone(), current_procedure);
+                                // Blocks left open in the procedure, like a tell without brackets, end with it
+                                while code_block_stack.len() > 1 && !matches!(code_block_stack.last().unwrap().code_block_type, CodeBlockType::PROCEDURE(_))
+                                {
+                                    close_code_block(&mut code_block_stack);
+                                }
+                                if code_block_stack.len() > 1
+                                {
+                                    procedures.insert(current_procedure_name.clone(), code_block_stack.pop().unwrap());
+                                }
                                 state = ParserState::READING_SYMBOL;
-                                current_procedure = CodeBlock::new(CodeBlockType::PROCEDURE(Procedure::new()));
-                                current_code_block = &mut current_procedure;
                                 current_procedure_name = MAIN_PROCEDURE_NAME.clone();
                             }
                             "repeat" =>
                             {
                                 let parsed_expression = read_expression(&mut code_iterator);
                                 let loop_repeats = parsed_expression.iter().map(|s| s.as_str()).collect();
-                                println!("{:?}", loop_repeats);
-                                let mut new_loop = CodeBlock::new(CodeBlockType::LOOP(Loop::new(Expression::new(loop_repeats))));
-                                current_code_block.add_instruction(ParserSymbol::CODE_BLOCK(new_loop));
-                                let last_instruction;
-                                {
-                                    let last_code_block_ref = current_code_block.last_instruction();
-                                    if let ParserSymbol::CODE_BLOCK(last_code_block) = last_code_block_ref 
-                                    {
-                                        last_instruction = last_code_block;
-                                    } else { panic!(); }
-                                }
-                                current_code_block = last_instruction;
+                                code_block_stack.push(CodeBlock::new(CodeBlockType::LOOP(Loop::new(Expression::new(loop_repeats)))));
                             }
                             "if" =>
                             {
                                 let parsed_expression = read_expression(&mut code_iterator);
                                 let if_condition = parsed_expression.iter().map(|s| s.as_str()).collect();
-                                println!("{:?}", if_condition);
-                                let mut new_if = CodeBlock::new(CodeBlockType::IF(If::new(Expression::new(if_condition))));
-                                current_code_block.add_instruction(ParserSymbol::CODE_BLOCK(new_if));
-                                let last_instruction;
-                                {
-                                    let last_code_block_ref = current_code_block.last_instruction();
-                                    if let ParserSymbol::CODE_BLOCK(last_code_block) = last_code_block_ref 
-                                    {
-                                        last_instruction = last_code_block;
-                                    } else { panic!(); }
-                                }
-                                current_code_block = last_instruction;
+                                code_block_stack.push(CodeBlock::new(CodeBlockType::IF(If::new(Expression::new(if_condition)))));
+                            }
+                            "ask" | "tell" =>
+                            {
+                                let turtles = read_turtle_list(&mut code_iterator);
+                                let restore_turtle = current_symbol == "ask";
+                                code_block_stack.push(CodeBlock::new(CodeBlockType::ASK(Ask::new(turtles, restore_turtle))));
                             }
                             _ => {}
                         }
                     }
                     else if COMMANDS_NO_PARAMETER.contains_key(current_symbol.as_str())
                     {
-                        current_code_block.add_instruction(ParserSymbol::COMMAND(Command::new(*COMMANDS_NO_PARAMETER.get(current_symbol.as_str()).unwrap(),
-                                                                               Expression::new(vec!()))));
+                        code_block_stack.last_mut().unwrap().add_instruction(ParserSymbol::COMMAND(Command::new(*COMMANDS_NO_PARAMETER.get(current_symbol.as_str()).unwrap(),
+                                                                                                  Expression::new(vec!()))));
                     }
                     else if COMMANDS.contains_key(current_symbol.as_str())
                     {
                         let parameter = read_expression(&mut code_iterator);
                         let parameter_str = parameter.iter().map(|s| s.as_str()).collect();
-                        println!("{:?}", parameter_str);
-                        current_code_block.add_instruction(ParserSymbol::COMMAND(Command::new(*COMMANDS.get(current_symbol.as_str()).unwrap(),
-                                                                               Expression::new(parameter_str))));
+                        code_block_stack.last_mut().unwrap().add_instruction(ParserSymbol::COMMAND(Command::new(*COMMANDS.get(current_symbol.as_str()).unwrap(),
+                                                                                                  Expression::new(parameter_str))));
                     }
                     else if procedures_info.contains_key(current_symbol.as_str())
                     {
@@ -298,16 +329,15 @@ pub fn parse_logo_code(code: &str) -> HashMap<String, CodeBlock>
                         {
                             let parameter = read_expression(&mut code_iterator);
                             let parameter_str = parameter.iter().map(|s| s.as_str()).collect();
-                            println!("{:?}", parameter_str);
                             call_parameters.push_back(Expression::new(parameter_str));
                         }
-                        current_code_block.add_instruction(ParserSymbol::PROCEDURE_CALL(ProcedureCall{ procedure_name: current_symbol.clone(), parameter_expressions: call_parameters}));
+                        code_block_stack.last_mut().unwrap().add_instruction(ParserSymbol::PROCEDURE_CALL(ProcedureCall{ procedure_name: current_symbol.clone(), parameter_expressions: call_parameters}));
                     }
                     current_symbol.clear();
                 }
                 else if ch == ']'
                 {
-                    current_code_block = &mut current_procedure;
+                    close_code_block(&mut code_block_stack);
                 }
                 else if ch == '[' {}
                 else 
@@ -319,9 +349,7 @@ pub fn parse_logo_code(code: &str) -> HashMap<String, CodeBlock>
             {
                 if ch.is_whitespace()
                 {
-                    //procedures.insert(current_symbol.clone(), CodeBlock::new(CodeBlockType::PROCEDURE(Procedure::new())));
                     current_procedure_name = current_symbol.clone();
-                    //code_block_stack.push(procedures.get_mut(&current_symbol).unwrap());
                     current_symbol.clear();
                     state = ParserState::READING_PROCEDURE_PARAMETERS;
                 }
@@ -338,16 +366,16 @@ pub fn parse_logo_code(code: &str) -> HashMap<String, CodeBlock>
                 }
                 else if ch.is_whitespace()
                 {
-                    if current_symbol.len() > 0
+                    if !current_symbol.is_empty()
                     {
-                        if let CodeBlockType::PROCEDURE(ref mut procedure) = &mut current_code_block.code_block_type 
+                        if let CodeBlockType::PROCEDURE(ref mut procedure) = &mut code_block_stack.last_mut().unwrap().code_block_type 
                         {
                             procedure.call_parameters.push_back(current_symbol[1..].to_string());
                         }
                         current_symbol.clear();
                     }
                 }
-                else if current_symbol.len() > 0
+                else if !current_symbol.is_empty()
                 {
                     current_symbol.push(ch);
                 }
@@ -355,20 +383,63 @@ pub fn parse_logo_code(code: &str) -> HashMap<String, CodeBlock>
                 {
                     state = ParserState::READING_SYMBOL;
                     current_symbol.push(ch);
-                    if let CodeBlockType::PROCEDURE(ref mut procedure) = &mut current_code_block.code_block_type 
+                    if let CodeBlockType::PROCEDURE(ref mut procedure) = &mut code_block_stack.last_mut().unwrap().code_block_type 
                     {
                         procedures_info.insert(current_procedure_name.clone(), procedure.call_parameters.len() as i32);
                     }
                 }
             }
         }
-        println!("{}",current_symbol.clone());
     }
-    procedures.insert(current_procedure_name.clone(), current_procedure);
+    // Blocks left open at the end of the code are closed implicitly
+    while code_block_stack.len() > 1 && !matches!(code_block_stack.last().unwrap().code_block_type, CodeBlockType::PROCEDURE(_))
+    {
+        close_code_block(&mut code_block_stack);
+    }
+    procedures.insert(current_procedure_name.clone(), code_block_stack.pop().unwrap());
+    if let Some(main_procedure) = code_block_stack.pop()
+    {
+        procedures.insert(MAIN_PROCEDURE_NAME, main_procedure);
+    }
 
     procedures
 }
 
+// Moves the innermost open block into the block enclosing it, procedures are only closed by "end"
+fn close_code_block(code_block_stack: &mut Vec<CodeBlock>)
+{
+    if let CodeBlockType::PROCEDURE(_) = code_block_stack.last().unwrap().code_block_type { return; }
+
+    let code_block = code_block_stack.pop().unwrap();
+    code_block_stack.last_mut().unwrap().add_instruction(ParserSymbol::CODE_BLOCK(code_block));
+}
+
+// Reads the turtles of ask and tell, either a single expression or a list of them in square brackets
+fn read_turtle_list(iter: &mut std::iter::Peekable<std::str::Chars<'_>>) -> Vec<Expression>
+{
+    while iter.next_if(|next_char| next_char.is_whitespace()).is_some() {}
+
+    let mut turtles = vec!();
+    if iter.next_if_eq(&'[').is_some()
+    {
+        loop
+        {
+            while iter.next_if(|next_char| next_char.is_whitespace()).is_some() {}
+            if iter.peek().is_none() || iter.next_if_eq(&']').is_some() { break; }
+
+            let parameter = read_expression(iter);
+            if parameter.is_empty() { break; }
+            turtles.push(Expression::new(parameter.iter().map(|s| s.as_str()).collect()));
+        }
+    }
+    else
+    {
+        let parameter = read_expression(iter);
+        turtles.push(Expression::new(parameter.iter().map(|s| s.as_str()).collect()));
+    }
+    turtles
+}
+
 pub fn read_expression(iter: &mut std::iter::Peekable<std::str::Chars<'_>>) -> Vec<String>
 {
     let mut reading_variable = false;
@@ -381,7 +452,7 @@ pub fn read_expression(iter: &mut std::iter::Peekable<std::str::Chars<'_>>) -> V
         if next_char.is_whitespace() 
         {
             reading_variable = false;
-            if current_symbol.len() > 0
+            if !current_symbol.is_empty()
             {
                 can_read_value = false;
                 result.push(current_symbol.clone());
@@ -405,7 +476,7 @@ pub fn read_expression(iter: &mut std::iter::Peekable<std::str::Chars<'_>>) -> V
             {
                 reading_variable = false;
                 can_read_value = true;
-                if current_symbol.len() > 0
+                if !current_symbol.is_empty()
                 {
                     result.push(current_symbol.clone());
                     current_symbol.clear();
@@ -417,24 +488,37 @@ pub fn read_expression(iter: &mut std::iter::Peekable<std::str::Chars<'_>>) -> V
                 next_char == '(' || next_char == ')'
         {
             reading_variable = false;
-            can_read_value = true;
-            if current_symbol.len() > 0
+            // A minus after a closing bracket is a subtraction, not the sign of a number
+            can_read_value = next_char != ')';
+            if !current_symbol.is_empty()
             {
                 result.push(current_symbol.clone());
                 current_symbol.clear();
             }
             result.push(next_char.to_string());
         }
+        else if can_read_value && !reading_variable && current_symbol.is_empty() && iter.clone().take_while(|c| c.is_alphanumeric()).eq("random".chars())
+        {
+            // random takes the rest of the expression as its bound, wherever a value can start
+            for _ in 0.."random".len() { iter.next(); }
+            result.push("random".to_string());
+            continue;
+        }
         else { break; }
         iter.next();
     }
+    if !current_symbol.is_empty()
+    {
+        result.push(current_symbol.clone());
+        current_symbol.clear();
+    }
 
-    // Function command (pick or random)
-    if result.len() == 0
+    // Function command (pick)
+    if result.is_empty()
     {
-        while let Some(next_char) = iter.next()
+        for next_char in iter.by_ref()
         {
-            if next_char == ' ' 
+            if next_char.is_whitespace() 
             { 
                 result.push(current_symbol.clone());
                 break; 
@@ -442,21 +526,19 @@ pub fn read_expression(iter: &mut std::iter::Peekable<std::str::Chars<'_>>) -> V
             current_symbol.push(next_char);
         }
 
-        if current_symbol == "random"
-        {
-            let mut parameter = read_expression(iter);
-            result.append(&mut parameter);
-        }
-        else if current_symbol == "pick"
+        if current_symbol == "pick"
         {
             current_symbol.clear();
-            while let Some(next_char) = iter.next()
+            for next_char in iter.by_ref()
             {
-                if next_char == ']' { break; }
-                if next_char == ' ' && current_symbol.len() > 0
+                if next_char.is_whitespace() || next_char == ']'
                 {
-                    result.push(current_symbol.clone());
-                    current_symbol.clear();
+                    if !current_symbol.is_empty()
+                    {
+                        result.push(current_symbol.clone());
+                        current_symbol.clear();
+                    }
+                    if next_char == ']' { break; }
                 }
                 else if next_char != '['
                 {
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use lazy_static::lazy_static;
+
+use crate::drawing::{ BoundingBox, Drawing, Sprite };
+
+pub mod svg;
+pub mod stroke_font;
+pub mod plotter;
+pub mod gcode;
+pub mod hpgl;
+pub mod vector;
+pub mod pdf;
+pub mod eps;
+pub mod raster;
+pub mod preview;
+pub mod json;
+pub mod html;
+#[cfg(feature = "png")]
+pub mod png;
+#[cfg(feature = "gif")]
+pub mod gif;
+
+lazy_static!
+{
+    static ref NAMED_COLORS: HashMap<&'static str, (u8, u8, u8)> =
+    {
+        let mut map = HashMap::new();
+        map.insert("black", (0, 0, 0));
+        map.insert("white", (255, 255, 255));
+        map.insert("red", (255, 0, 0));
+        map.insert("lime", (0, 255, 0));
+        map.insert("blue", (0, 0, 255));
+        map.insert("yellow", (255, 255, 0));
+        map.insert("cyan", (0, 255, 255));
+        map.insert("aqua", (0, 255, 255));
+        map.insert("magenta", (255, 0, 255));
+        map.insert("fuchsia", (255, 0, 255));
+        map.insert("silver", (192, 192, 192));
+        map.insert("gray", (128, 128, 128));
+        map.insert("grey", (128, 128, 128));
+        map.insert("maroon", (128, 0, 0));
+        map.insert("olive", (128, 128, 0));
+        map.insert("green", (0, 128, 0));
+        map.insert("purple", (128, 0, 128));
+        map.insert("teal", (0, 128, 128));
+        map.insert("navy", (0, 0, 128));
+        map.insert("orange", (255, 165, 0));
+        map.insert("brown", (165, 42, 42));
+        map.insert("tan", (210, 180, 140));
+        map.insert("aquamarine", (127, 255, 212));
+        map.insert("salmon", (250, 128, 114));
+        map.insert("violet", (238, 130, 238));
+        map.insert("pink", (255, 192, 203));
+        map.insert("gold", (255, 215, 0));
+        map.insert("indigo", (75, 0, 130));
+        map.insert("turquoise", (64, 224, 208));
+        map.insert("beige", (245, 245, 220));
+        map.insert("coral", (255, 127, 80));
+        map.insert("crimson", (220, 20, 60));
+        map.insert("khaki", (240, 230, 140));
+        map.insert("lavender", (230, 230, 250));
+        map.insert("orchid", (218, 112, 214));
+        map.insert("plum", (221, 160, 221));
+        map.insert("skyblue", (135, 206, 235));
+        map.insert("darkgreen", (0, 100, 0));
+        map.insert("darkblue", (0, 0, 139));
+        map.insert("darkred", (139, 0, 0));
+        map.insert("lightblue", (173, 216, 230));
+        map.insert("lightgreen", (144, 238, 144));
+        map.insert("lightgray", (211, 211, 211));
+        map
+    };
+}
+
+// Turns the drawing produced by the interpreter into the contents of an output file
+pub trait Renderer
+{
+    fn render(&self, drawing: &Drawing) -> Vec<u8>;
+}
+
+// Understands the colours the interpreter passes through from setcolor: names, #rrggbb, #rgb and rgb(r,g,b)
+pub fn parse_color(color: &str) -> Option<(u8, u8, u8)>
+{
+    let color = color.trim().to_lowercase();
+    if let Some(named_color) = NAMED_COLORS.get(color.as_str())
+    {
+        return Some(*named_color);
+    }
+    if let Some(hex) = color.strip_prefix('#')
+    {
+        let digits: Vec<u8> = hex.chars().map(|c| c.to_digit(16).map(|digit| digit as u8)).collect::<Option<Vec<u8>>>()?;
+        return match digits.len()
+        {
+            3 => Some((digits[0] * 17, digits[1] * 17, digits[2] * 17)),
+            6 => Some((digits[0] * 16 + digits[1], digits[2] * 16 + digits[3], digits[4] * 16 + digits[5])),
+            _ => None
+        };
+    }
+    if let Some(components) = color.strip_prefix("rgb(").and_then(|rest| rest.strip_suffix(')'))
+    {
+        let components: Vec<u8> = components.split(',').map(|component| component.trim().parse::<u8>().ok()).collect::<Option<Vec<u8>>>()?;
+        if components.len() == 3
+        {
+            return Some((components[0], components[1], components[2]));
+        }
+    }
+    None
+}
+
+// Part of the canvas that gets rendered, either all of it or the drawing with a margin around
+pub fn viewport(drawing: &Drawing, fit_to_content: bool, margin: f32) -> BoundingBox
+{
+    match drawing.get_bounding_box().filter(|_| fit_to_content)
+    {
+        Some(mut bounding_box) =>
+        {
+            bounding_box.expand(margin);
+            bounding_box
+        }
+        None =>
+        {
+            let mut canvas = BoundingBox::from_point(0.0, 0.0);
+            canvas.include_point(drawing.width as f32, drawing.height as f32);
+            canvas
+        }
+    }
+}
+
+// Outlines of the SVG markup of a turtle shape, made of polygon, circle, ellipse and rect elements with only their geometry
+pub fn shape_outlines(markup: &str) -> Result<Vec<Vec<(f32, f32)>>, String>
+{
+    let ellipse = |center_x: f32, center_y: f32, radius_x: f32, radius_y: f32|
+    {
+        (0..32).map(|step| (step as f32 * std::f32::consts::TAU / 32.0).sin_cos())
+               .map(|(sin, cos)| (center_x + radius_x * cos, center_y + radius_y * sin)).collect::<Vec<(f32, f32)>>()
+    };
+    let mut outlines = vec!();
+    let mut rest = markup.trim();
+    while !rest.is_empty()
+    {
+        let (element, after) = rest.strip_prefix('<').and_then(|element| element.split_once("/>"))
+                                   .ok_or(format!("Expected an element ending with /> at: {rest}"))?;
+        rest = after.trim_start();
+        let (name, mut attributes_text) = element.split_once(char::is_whitespace).unwrap_or((element, ""));
+        let mut attributes: HashMap<&str, &str> = HashMap::new();
+        while let Some((attribute, after)) = attributes_text.split_once("=\"")
+        {
+            let (value, after) = after.split_once('"').ok_or(format!("Unterminated value of {}", attribute.trim()))?;
+            attributes.insert(attribute.trim(), value);
+            attributes_text = after;
+        }
+        if !attributes_text.trim().is_empty()
+        {
+            return Err(format!("Invalid attributes of {name}: {}", attributes_text.trim()));
+        }
+        let allowed: &[&str] = match name
+        {
+            "polygon" => &["points"],
+            "circle" => &["cx", "cy", "r"],
+            "ellipse" => &["cx", "cy", "rx", "ry"],
+            "rect" => &["x", "y", "width", "height"],
+            _ => return Err(format!("Unsupported element of a turtle shape: {name}"))
+        };
+        if let Some(attribute) = attributes.keys().find(|attribute| !allowed.contains(attribute))
+        {
+            return Err(format!("Unsupported attribute of {name}: {attribute}"));
+        }
+        let number = |attribute: &str| -> Result<f32, String>
+        {
+            attributes.get(attribute).map_or(Ok(0.0), |value| value.trim().parse::<f32>().ok().filter(|number| number.is_finite())
+                                                                     .ok_or(format!("Invalid {attribute} of {name}: {value}")))
+        };
+        outlines.push(match name
+        {
+            "polygon" =>
+            {
+                let points = attributes.get("points").copied().unwrap_or("");
+                let coordinates = points.split(|character: char| character == ',' || character.is_whitespace()).filter(|number| !number.is_empty())
+                                        .map(|number| number.parse::<f32>().ok().filter(|number| number.is_finite()))
+                                        .collect::<Option<Vec<f32>>>().filter(|coordinates| coordinates.len() >= 6 && coordinates.len() % 2 == 0)
+                                        .ok_or(format!("Invalid points of polygon: {points}"))?;
+                coordinates.chunks(2).map(|point| (point[0], point[1])).collect()
+            }
+            "circle" => ellipse(number("cx")?, number("cy")?, number("r")?, number("r")?),
+            "ellipse" => ellipse(number("cx")?, number("cy")?, number("rx")?, number("ry")?),
+            _ =>
+            {
+                let (x, y, width, height) = (number("x")?, number("y")?, number("width")?, number("height")?);
+                vec!((x, y), (x + width, y), (x + width, y + height), (x, y + height))
+            }
+        });
+    }
+    if outlines.is_empty()
+    {
+        return Err("A turtle shape needs at least one element".to_string());
+    }
+    Ok(outlines)
+}
+
+// Outlines of the turtle shape placed on the canvas
+pub fn sprite_outlines(sprite: &Sprite) -> Vec<Vec<(f32, f32)>>
+{
+    let outlines = shape_outlines(&sprite.shape).unwrap_or_default();
+    let (sin, cos) = sprite.rotation_angle.to_radians().sin_cos();
+    outlines.into_iter().map(|outline| outline.into_iter().map(|(x, y)| (sprite.x + x * cos - y * sin, sprite.y + x * sin + y * cos)).collect()).collect()
+}
\ No newline at end of file
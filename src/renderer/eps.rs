@@ -0,0 +1,96 @@
+use crate::drawing::Drawing;
+use crate::renderer::{ viewport, Renderer };
+use crate::renderer::vector::{ number, color, vector_items, PageSetup, PathCommand, VectorItem };
+
+// Encapsulated PostScript placed as it would be on the page, PostScript has no transparency
+// so the turtles are lightened instead and reversing lines are drawn with their colour
+pub struct EpsRenderer
+{
+    pub fit_to_content: bool,  // print the drawing instead of the whole canvas
+    pub margin: f32,           // space left around the drawing when fitting to content
+    pub page: PageSetup
+}
+impl Default for EpsRenderer
+{
+    fn default() -> Self
+    {
+        Self { fit_to_content: false, margin: 10.0, page: PageSetup::default() }
+    }
+}
+
+fn postscript_string(text: &str) -> String
+{
+    let mut string = "(".to_string();
+    for character in text.chars()
+    {
+        match character
+        {
+            '(' | ')' | '\\' => { string.push('\\'); string.push(character); }
+            ' '..='~' => string.push(character),
+            _ => string.push('?')
+        }
+    }
+    string.push(')');
+    string
+}
+
+impl Renderer for EpsRenderer
+{
+    fn render(&self, drawing: &Drawing) -> Vec<u8>
+    {
+        let fit_to_content = self.fit_to_content && drawing.get_bounding_box().is_some();
+        let viewport = viewport(drawing, fit_to_content, self.margin);
+        let layout = self.page.layout(&viewport);
+        let picture = layout.picture;
+        let [a, b, c, d, e, f] = layout.matrix;
+
+        let mut eps = format!("%!PS-Adobe-3.0 EPSF-3.0\n%%BoundingBox: {} {} {} {}\n%%HiResBoundingBox: {} {} {} {}\n%%LanguageLevel: 2\n%%EndComments\n",
+                              picture.min_x.floor(), picture.min_y.floor(), picture.max_x.ceil(), picture.max_y.ceil(),
+                              number(picture.min_x), number(picture.min_y), number(picture.max_x), number(picture.max_y));
+        eps.push_str("/m { moveto } bind def /l { lineto } bind def /c { curveto } bind def /h { closepath } bind def\n");
+        eps.push_str(&format!("gsave\n[{} {} {} {} {} {}] concat\n{} {} {} {} rectclip\n1 setlinecap 1 setlinejoin\n",
+                              number(a), number(b), number(c), number(d), number(e), number(f),
+                              number(viewport.min_x), number(viewport.min_y), number(viewport.width()), number(viewport.height())));
+        for item in vector_items(drawing, &viewport, !fit_to_content)
+        {
+            match item
+            {
+                VectorItem::SHAPE(shape) =>
+                {
+                    eps.push_str("newpath\n");
+                    for command in shape.path.iter()
+                    {
+                        match command
+                        {
+                            PathCommand::MOVE(x, y) => eps.push_str(&format!("{} {} m\n", number(*x), number(*y))),
+                            PathCommand::LINE(x, y) => eps.push_str(&format!("{} {} l\n", number(*x), number(*y))),
+                            PathCommand::CURVE(x1, y1, x2, y2, x, y) =>
+                                eps.push_str(&format!("{} {} {} {} {} {} c\n", number(*x1), number(*y1), number(*x2), number(*y2), number(*x), number(*y))),
+                            PathCommand::CLOSE => eps.push_str("h\n")
+                        }
+                    }
+                    if let Some((red, green, blue)) = shape.fill
+                    {
+                        let lighten = |component: f32| component * shape.fill_opacity + 1.0 - shape.fill_opacity;
+                        let fill = if shape.pen.is_some() { "gsave fill grestore" } else { "fill" };
+                        eps.push_str(&format!("{} {} {} setrgbcolor {}\n", number(lighten(red)), number(lighten(green)), number(lighten(blue)), fill));
+                    }
+                    if let Some(pen) = &shape.pen
+                    {
+                        let (red, green, blue) = pen.color;
+                        eps.push_str(&format!("{} {} {} setrgbcolor {} setlinewidth stroke\n", number(red), number(green), number(blue), number(pen.width)));
+                    }
+                }
+                VectorItem::TEXT(text) =>
+                {
+                    let (red, green, blue) = color(&text.color);
+                    eps.push_str(&format!("gsave\n{} {} translate {} rotate 1 -1 scale\n/Helvetica findfont {} scalefont setfont\n{} {} {} setrgbcolor 0 0 moveto {} show\ngrestore\n",
+                                          number(text.x), number(text.y), number(text.rotation_angle), text.font_size,
+                                          number(red), number(green), number(blue), postscript_string(&text.text)));
+                }
+            }
+        }
+        eps.push_str("grestore\nshowpage\n%%EOF\n");
+        eps.into_bytes()
+    }
+}
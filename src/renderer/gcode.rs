@@ -0,0 +1,43 @@
+use crate::drawing::Drawing;
+use crate::renderer::{ plotter, viewport, Renderer };
+
+pub struct GcodeRenderer
+{
+    pub fit_to_content: bool,        // start at the drawing instead of the corner of the canvas
+    pub margin: f32,                 // space left around the drawing when fitting to content
+    pub millimetres_per_unit: f32,   // size on paper of a canvas pixel
+    pub feed_rate: f32,              // drawing speed in mm/min, moves with the pen up go at full speed
+    pub pen_up: String,              // commands lifting the pen
+    pub pen_down: String,            // commands lowering the pen
+    pub optimize_travel: bool        // reorder the strokes to shorten the moves with the pen up
+}
+impl Default for GcodeRenderer
+{
+    fn default() -> Self
+    {
+        Self { fit_to_content: false, margin: 10.0, millimetres_per_unit: 0.25, feed_rate: 1500.0,
+               pen_up: "G0 Z5".to_string(), pen_down: "G1 Z0".to_string(), optimize_travel: true }
+    }
+}
+
+impl Renderer for GcodeRenderer
+{
+    fn render(&self, drawing: &Drawing) -> Vec<u8>
+    {
+        let fit_to_content = self.fit_to_content && drawing.get_bounding_box().is_some();
+        let viewport = viewport(drawing, fit_to_content, self.margin);
+        let mut gcode = format!("G21 ; millimetres\nG90 ; absolute coordinates\n{}\n", self.pen_up);
+        for stroke in plotter::plot_strokes(drawing, &viewport, self.millimetres_per_unit, self.optimize_travel)
+        {
+            gcode.push_str(&format!("G0 X{:.3} Y{:.3}\n{}\n", stroke[0].0, stroke[0].1, self.pen_down));
+            gcode.push_str(&format!("G1 X{:.3} Y{:.3} F{}\n", stroke[1].0, stroke[1].1, self.feed_rate));
+            for (x, y) in stroke.iter().skip(2)
+            {
+                gcode.push_str(&format!("G1 X{:.3} Y{:.3}\n", x, y));
+            }
+            gcode.push_str(&format!("{}\n", self.pen_up));
+        }
+        gcode.push_str("G0 X0 Y0\nM2\n");
+        gcode.into_bytes()
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use crate::drawing::{ Drawing, TICKS_PER_SECOND };
+use crate::renderer::{ raster, viewport, Renderer };
+
+const LAST_FRAME_DELAY: f32 = 3.0; // seconds the finished drawing stays before the animation starts over
+const QUANTIZATION_SPEED: i32 = 10; // 1 gives the best colours, 30 is the fastest
+const MAX_SIZE: f32 = 65534.0;      // GIF sizes are 16 bits, one pixel is left for rounding the scaled size up
+
+// Animated GIF with a frame for every wait, the turtles only show up on the finished drawing
+pub struct GifRenderer
+{
+    pub fit_to_content: bool,  // crop the frames to the drawing instead of the whole canvas
+    pub margin: f32            // space left around the drawing when fitting to content
+}
+impl Default for GifRenderer
+{
+    fn default() -> Self
+    {
+        Self { fit_to_content: false, margin: 10.0 }
+    }
+}
+
+// Smallest rectangle holding every pixel that differs from the previous frame, a single pixel when none does
+fn changed_area(previous_rgb: &[u8], rgb: &[u8], width: usize, height: usize) -> (usize, usize, usize, usize)
+{
+    let (mut left, mut top, mut right, mut bottom) = (width, height, 0, 0);
+    for y in 0..height
+    {
+        for x in 0..width
+        {
+            let index = (y * width + x) * 3;
+            if previous_rgb[index..index + 3] != rgb[index..index + 3]
+            {
+                (left, top, right, bottom) = (left.min(x), top.min(y), right.max(x + 1), bottom.max(y + 1));
+            }
+        }
+    }
+    if left >= right { (0, 0, 1, 1) } else { (left, top, right, bottom) }
+}
+
+// Line drawings rarely use more than the 256 colours a frame can have, quantizing is only needed when they do
+fn frame_with_palette(width: u16, height: u16, rgb: &[u8]) -> gif::Frame<'static>
+{
+    let mut palette_indices: HashMap<&[u8], u8> = HashMap::new();
+    let mut palette: Vec<u8> = vec!();
+    let mut indexed_pixels: Vec<u8> = Vec::with_capacity(rgb.len() / 3);
+    for pixel in rgb.chunks_exact(3)
+    {
+        let index = match palette_indices.get(pixel)
+        {
+            Some(index) => *index,
+            None if palette_indices.len() < 256 =>
+            {
+                let index = palette_indices.len() as u8;
+                palette_indices.insert(pixel, index);
+                palette.extend_from_slice(pixel);
+                index
+            }
+            None => return gif::Frame::from_rgb_speed(width, height, rgb, QUANTIZATION_SPEED)
+        };
+        indexed_pixels.push(index);
+    }
+    gif::Frame::from_palette_pixels(width, height, indexed_pixels, palette, None)
+}
+
+impl Renderer for GifRenderer
+{
+    fn render(&self, drawing: &Drawing) -> Vec<u8>
+    {
+        let fit_to_content = self.fit_to_content && drawing.get_bounding_box().is_some();
+        let viewport = viewport(drawing, fit_to_content, self.margin);
+
+        // Drawings larger than a GIF can hold are scaled down instead of having their size truncated
+        let scale = (MAX_SIZE / viewport.width().max(viewport.height())).min(1.0);
+        let (width, height) = raster::raster_size(&viewport, scale);
+        let mut gif_data: Vec<u8> = vec!();
+        {
+            let mut encoder = gif::Encoder::new(&mut gif_data, width as u16, height as u16, &[]).expect("Unable to encode the GIF header");
+            encoder.set_repeat(gif::Repeat::Infinite).expect("Unable to encode the GIF header");
+            let mut elapsed_ticks = 0;
+            let mut previous_rgb: Option<Vec<u8>> = None;
+            raster::rasterize_frames(drawing, &viewport, scale, !fit_to_content, |frame_raster, ticks|
+            {
+                // Delays are in hundredths of a second, rounding the time elapsed keeps the frames from drifting
+                let delay = if ticks == 0
+                {
+                    (LAST_FRAME_DELAY * 100.0) as u16
+                }
+                else
+                {
+                    let start = (elapsed_ticks as f32 * 100.0 / TICKS_PER_SECOND).round();
+                    elapsed_ticks += ticks;
+                    ((elapsed_ticks as f32 * 100.0 / TICKS_PER_SECOND).round() - start) as u16
+                };
+                let rgb = frame_raster.to_rgb8();
+                let (left, top, right, bottom) = match &previous_rgb
+                {
+                    Some(previous_rgb) => changed_area(previous_rgb, &rgb, width, height),
+                    None => (0, 0, width, height)
+                };
+                let area_rgb: Vec<u8> = (top..bottom).flat_map(|y| rgb[(y * width + left) * 3..(y * width + right) * 3].iter().copied()).collect();
+                let mut frame = frame_with_palette((right - left) as u16, (bottom - top) as u16, &area_rgb);
+                frame.left = left as u16;
+                frame.top = top as u16;
+                frame.delay = delay;
+                frame.dispose = gif::DisposalMethod::Keep;
+                encoder.write_frame(&frame).expect("Unable to encode a GIF frame");
+                previous_rgb = Some(rgb);
+            });
+        }
+        gif_data
+    }
+}
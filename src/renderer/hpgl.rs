@@ -0,0 +1,38 @@
+use crate::drawing::Drawing;
+use crate::renderer::{ plotter, viewport, Renderer };
+
+const PLOTTER_UNITS_PER_MILLIMETRE: f32 = 40.0;
+
+pub struct HpglRenderer
+{
+    pub fit_to_content: bool,        // start at the drawing instead of the corner of the canvas
+    pub margin: f32,                 // space left around the drawing when fitting to content
+    pub millimetres_per_unit: f32,   // size on paper of a canvas pixel
+    pub optimize_travel: bool        // reorder the strokes to shorten the moves with the pen up
+}
+impl Default for HpglRenderer
+{
+    fn default() -> Self
+    {
+        Self { fit_to_content: false, margin: 10.0, millimetres_per_unit: 0.25, optimize_travel: true }
+    }
+}
+
+impl Renderer for HpglRenderer
+{
+    fn render(&self, drawing: &Drawing) -> Vec<u8>
+    {
+        let fit_to_content = self.fit_to_content && drawing.get_bounding_box().is_some();
+        let viewport = viewport(drawing, fit_to_content, self.margin);
+        let plotter_point = |(x, y): (f32, f32)| format!("{},{}", (x * PLOTTER_UNITS_PER_MILLIMETRE).round() as i32, (y * PLOTTER_UNITS_PER_MILLIMETRE).round() as i32);
+
+        let mut hpgl = "IN;SP1;\n".to_string();
+        for stroke in plotter::plot_strokes(drawing, &viewport, self.millimetres_per_unit, self.optimize_travel)
+        {
+            let points: Vec<String> = stroke.iter().skip(1).map(|point| plotter_point(*point)).collect();
+            hpgl.push_str(&format!("PU{};PD{};\n", plotter_point(stroke[0]), points.join(",")));
+        }
+        hpgl.push_str("PU0,0;SP0;\n");
+        hpgl.into_bytes()
+    }
+}
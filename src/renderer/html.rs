@@ -0,0 +1,280 @@
+use crate::drawing::{ Drawing, DEFAULT_BACKGROUND_COLOR, TICKS_PER_SECOND };
+use crate::renderer::json::{ action_json, json_number, json_string };
+use crate::renderer::{ viewport, Renderer };
+
+// Replays the turtle actions on a canvas in the time given by the waits of the program,
+// one without any wait takes a quarter of a tick per action instead so that it is seen being drawn
+const PLAYER: &str = r#"const paper = document.getElementById("paper");
+const overlay = document.getElementById("turtles");
+const [viewLeft, viewTop, viewWidth, viewHeight] = drawing.viewport;
+for (const canvas of [paper, overlay])
+{
+    canvas.width = Math.ceil(viewWidth);
+    canvas.height = Math.ceil(viewHeight);
+}
+const pen = paper.getContext("2d", { willReadFrequently: true });
+const sprites = overlay.getContext("2d");
+const actions = drawing.actions;
+const actionTicks = actions.some(action => action.action == "wait") ? 0 : 0.25;
+const playButton = document.getElementById("play");
+const speedInput = document.getElementById("speed");
+
+let next, background, turtles, waiting, budget, playing = true;
+
+function restart()
+{
+    next = 0;
+    background = drawing.background;
+    turtles = new Map();
+    waiting = 0;
+    budget = 0;
+    clear();
+}
+
+function clear()
+{
+    pen.setTransform(1, 0, 0, 1, 0, 0);
+    pen.globalCompositeOperation = "source-over";
+    pen.fillStyle = background;
+    pen.fillRect(0, 0, paper.width, paper.height);
+    pen.setTransform(1, 0, 0, 1, -viewLeft, -viewTop);
+}
+
+function rgb(color)
+{
+    const scratch = document.createElement("canvas").getContext("2d");
+    scratch.fillStyle = color;
+    scratch.fillRect(0, 0, 1, 1);
+    return scratch.getImageData(0, 0, 1, 1).data;
+}
+
+// Only the pixels still showing the old background change colour
+function setBackground(color)
+{
+    const [oldRed, oldGreen, oldBlue] = rgb(background);
+    const [red, green, blue] = rgb(color);
+    const image = pen.getImageData(0, 0, paper.width, paper.height);
+    const pixels = image.data;
+    for (let i = 0; i < pixels.length; i += 4)
+    {
+        if (pixels[i] == oldRed && pixels[i + 1] == oldGreen && pixels[i + 2] == oldBlue)
+        {
+            pixels[i] = red;
+            pixels[i + 1] = green;
+            pixels[i + 2] = blue;
+        }
+    }
+    pen.putImageData(image, 0, 0);
+    background = color;
+}
+
+function stroke(action)
+{
+    pen.lineWidth = action.width;
+    pen.globalCompositeOperation = action.pen_mode == "reverse" ? "difference" : "source-over";
+    pen.strokeStyle = action.pen_mode == "erase" ? background : action.pen_mode == "reverse" ? "white" : action.color;
+    pen.stroke();
+    pen.globalCompositeOperation = "source-over";
+}
+
+function apply(action)
+{
+    const radians = degrees => degrees * Math.PI / 180;
+    switch (action.action)
+    {
+        case "move":
+            if (action.pen_down)
+            {
+                pen.beginPath();
+                pen.moveTo(...action.from);
+                pen.lineTo(...action.to);
+                stroke(action);
+            }
+            break;
+        case "label":
+            pen.save();
+            pen.translate(...action.to);
+            pen.rotate(radians(Math.round(action.heading - 90)));
+            pen.fillStyle = action.color;
+            pen.font = action.font_size + "px Arial";
+            pen.fillText(action.text, 0, 0);
+            pen.restore();
+            break;
+        case "wait":
+            waiting = action.ticks;
+            break;
+        case "clear":
+            clear();
+            break;
+        case "set_background":
+            setBackground(action.background);
+            break;
+    }
+    const visible = action.action == "show" || (action.action != "hide" && (turtles.get(action.turtle)?.visible ?? true));
+    turtles.set(action.turtle, { x: action.to[0], y: action.to[1], heading: action.heading, color: action.color, visible });
+}
+
+// Plays the actions fitting in the given number of ticks
+function advance(ticks)
+{
+    budget += ticks;
+    while (next < actions.length || waiting > 0)
+    {
+        if (waiting > 0)
+        {
+            const spent = Math.min(waiting, budget);
+            waiting -= spent;
+            budget -= spent;
+            if (waiting > 0) break;
+            continue;
+        }
+        if (budget < actionTicks) break;
+        budget -= actionTicks;
+        apply(actions[next++]);
+    }
+}
+
+function drawTurtles()
+{
+    sprites.setTransform(1, 0, 0, 1, 0, 0);
+    sprites.clearRect(0, 0, overlay.width, overlay.height);
+    sprites.setTransform(1, 0, 0, 1, -viewLeft, -viewTop);
+    if (drawing.border)
+    {
+        sprites.lineWidth = 10;
+        sprites.strokeStyle = "rgb(0,0,0)";
+        sprites.strokeRect(0, 0, drawing.width, drawing.height);
+    }
+    for (const turtle of turtles.values())
+    {
+        if (!turtle.visible) continue;
+        sprites.save();
+        sprites.translate(turtle.x, turtle.y);
+        sprites.rotate(turtle.heading * Math.PI / 180);
+        sprites.beginPath();
+        sprites.moveTo(0, -15);
+        sprites.lineTo(8, 5);
+        sprites.lineTo(-8, 5);
+        sprites.closePath();
+        sprites.globalAlpha = 0.5;
+        sprites.fillStyle = turtle.color;
+        sprites.fill();
+        sprites.globalAlpha = 1;
+        sprites.strokeStyle = turtle.color;
+        sprites.lineWidth = 1;
+        sprites.stroke();
+        sprites.restore();
+    }
+    const tick = next > 0 ? actions[next - 1].tick : 0;
+    document.getElementById("status").textContent = `${next} / ${actions.length} actions, ${(tick / drawing.ticks_per_second).toFixed(1)} s`;
+}
+
+function speed()
+{
+    return 2 ** Number(speedInput.value);
+}
+
+function showSpeed()
+{
+    document.getElementById("speed-value").textContent = speed() + "x";
+}
+
+function setPlaying(play)
+{
+    playing = play;
+    playButton.textContent = playing ? "Pause" : "Play";
+}
+
+let lastTime = null;
+function frame(time)
+{
+    if (playing && lastTime != null)
+    {
+        advance(Math.min(time - lastTime, 100) / 1000 * drawing.ticks_per_second * speed());
+        if (next >= actions.length && waiting <= 0) setPlaying(false);
+    }
+    lastTime = time;
+    drawTurtles();
+    requestAnimationFrame(frame);
+}
+
+playButton.addEventListener("click", () =>
+{
+    if (!playing && next >= actions.length) restart();
+    setPlaying(!playing);
+});
+document.getElementById("step").addEventListener("click", () =>
+{
+    setPlaying(false);
+    if (next < actions.length) apply(actions[next++]);
+    waiting = 0;
+});
+document.getElementById("restart").addEventListener("click", () =>
+{
+    restart();
+    setPlaying(true);
+});
+speedInput.addEventListener("input", showSpeed);
+
+showSpeed();
+restart();
+requestAnimationFrame(frame);"#;
+
+// Page with the turtle actions and a player drawing them on a canvas, with play, pause, step and speed controls
+pub struct HtmlRenderer
+{
+    pub fit_to_content: bool,  // show the drawing instead of the whole canvas
+    pub margin: f32            // space left around the drawing when fitting to content
+}
+impl Default for HtmlRenderer
+{
+    fn default() -> Self
+    {
+        Self { fit_to_content: false, margin: 10.0 }
+    }
+}
+
+impl Renderer for HtmlRenderer
+{
+    fn render(&self, drawing: &Drawing) -> Vec<u8>
+    {
+        let fit_to_content = self.fit_to_content && drawing.get_bounding_box().is_some();
+        let view = viewport(drawing, fit_to_content, self.margin);
+        let actions: Vec<String> = drawing.get_actions().iter().map(action_json).collect();
+        let drawing_json = format!("{{\"width\":{},\"height\":{},\"viewport\":[{},{},{},{}],\"border\":{},\"background\":{},\"ticks_per_second\":{},\"actions\":[\n{}\n]}}",
+                                   drawing.width, drawing.height, json_number(view.min_x), json_number(view.min_y), json_number(view.width()), json_number(view.height()),
+                                   !fit_to_content, json_string(DEFAULT_BACKGROUND_COLOR), json_number(TICKS_PER_SECOND), actions.join(",\n"));
+
+        format!("<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Logo drawing</title>
+<style>
+body {{ font-family: sans-serif; margin: 16px; }}
+#controls {{ margin-bottom: 8px; }}
+#stage {{ position: relative; display: inline-block; }}
+#stage canvas {{ display: block; }}
+#turtles {{ position: absolute; left: 0; top: 0; }}
+</style>
+</head>
+<body>
+<div id=\"controls\">
+<button id=\"play\">Pause</button>
+<button id=\"step\">Step</button>
+<button id=\"restart\">Restart</button>
+<label>Speed <input id=\"speed\" type=\"range\" min=\"-2\" max=\"6\" step=\"1\" value=\"0\"> <span id=\"speed-value\"></span></label>
+<span id=\"status\"></span>
+</div>
+<div id=\"stage\"><canvas id=\"paper\"></canvas><canvas id=\"turtles\"></canvas></div>
+<script>
+const drawing = {drawing_json};
+</script>
+<script>
+{PLAYER}
+</script>
+</body>
+</html>
+").into_bytes()
+    }
+}
@@ -0,0 +1,101 @@
+use crate::drawing::{ ActionKind, Drawing, PenMode, TurtleAction, DEFAULT_BACKGROUND_COLOR, TICKS_PER_SECOND };
+use crate::renderer::vector::number;
+use crate::renderer::Renderer;
+
+// Quoted JSON string, < is escaped too so that the text can sit in a script element
+pub fn json_string(text: &str) -> String
+{
+    let mut json = String::from("\"");
+    for character in text.chars()
+    {
+        match character
+        {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            '<' => json.push_str("\\u003c"),
+            character if (character as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", character as u32)),
+            character => json.push(character)
+        }
+    }
+    json.push('"');
+    json
+}
+
+// Numbers with three decimals at most, JSON has no infinity nor NaN
+pub fn json_number(value: f32) -> String
+{
+    if value.is_finite() { number(value) } else { "null".to_string() }
+}
+
+fn json_point((x, y): (f32, f32)) -> String
+{
+    format!("[{},{}]", json_number(x), json_number(y))
+}
+
+fn action_name(kind: &ActionKind) -> &'static str
+{
+    match kind
+    {
+        ActionKind::MOVE => "move",
+        ActionKind::TURN => "turn",
+        ActionKind::LABEL{ .. } => "label",
+        ActionKind::WAIT{ .. } => "wait",
+        ActionKind::CLEAR => "clear",
+        ActionKind::HOME => "home",
+        ActionKind::SET_BACKGROUND{ .. } => "set_background",
+        ActionKind::SHOW => "show",
+        ActionKind::HIDE => "hide"
+    }
+}
+
+// One object on a single line, with the turtle state first and the fields of the kind of action last
+pub fn action_json(action: &TurtleAction) -> String
+{
+    let pen_mode = match action.pen_mode
+    {
+        PenMode::PAINT => "paint",
+        PenMode::ERASE => "erase",
+        PenMode::REVERSE => "reverse"
+    };
+    let mut json = format!("{{\"action\":\"{}\",\"turtle\":{},\"tick\":{},\"from\":{},\"to\":{},\"heading\":{},\"pen_down\":{},\"pen_mode\":\"{}\",\"color\":{},\"width\":{}",
+                           action_name(&action.kind), action.turtle, action.tick, json_point(action.from), json_point(action.to), json_number(action.heading),
+                           action.pen_down, pen_mode, json_string(&action.color), json_number(action.width));
+    match &action.kind
+    {
+        ActionKind::LABEL{ text, font_size } => json.push_str(&format!(",\"text\":{},\"font_size\":{}", json_string(text), font_size)),
+        ActionKind::WAIT{ ticks } => json.push_str(&format!(",\"ticks\":{}", ticks)),
+        ActionKind::SET_BACKGROUND{ color } => json.push_str(&format!(",\"background\":{}", json_string(color))),
+        _ => {}
+    }
+    json.push('}');
+    json
+}
+
+// Log of every turtle action along with the canvas
+#[derive(Default)]
+pub struct JsonRenderer;
+
+impl Renderer for JsonRenderer
+{
+    fn render(&self, drawing: &Drawing) -> Vec<u8>
+    {
+        let actions: Vec<String> = drawing.get_actions().iter().map(action_json).collect();
+        format!("{{\"width\":{},\"height\":{},\"background\":{},\"ticks_per_second\":{},\"duration\":{},\"actions\":[\n{}\n]}}\n",
+                drawing.width, drawing.height, json_string(DEFAULT_BACKGROUND_COLOR), json_number(TICKS_PER_SECOND), drawing.get_duration(), actions.join(",\n")).into_bytes()
+    }
+}
+
+// The same log as JSON Lines, one action per line
+#[derive(Default)]
+pub struct JsonLinesRenderer;
+
+impl Renderer for JsonLinesRenderer
+{
+    fn render(&self, drawing: &Drawing) -> Vec<u8>
+    {
+        drawing.get_actions().iter().map(|action| action_json(action) + "\n").collect::<String>().into_bytes()
+    }
+}
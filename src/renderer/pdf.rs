@@ -0,0 +1,145 @@
+use crate::drawing::Drawing;
+use crate::renderer::{ viewport, Renderer };
+use crate::renderer::vector::{ number, color, vector_items, PageSetup, PathCommand, Shape, VectorItem };
+
+pub struct PdfRenderer
+{
+    pub fit_to_content: bool,  // print the drawing instead of the whole canvas
+    pub margin: f32,           // space left around the drawing when fitting to content
+    pub page: PageSetup
+}
+impl Default for PdfRenderer
+{
+    fn default() -> Self
+    {
+        Self { fit_to_content: false, margin: 10.0, page: PageSetup::default() }
+    }
+}
+
+// Labels are set in Helvetica, one of the fonts every PDF reader has, so the text stays selectable
+fn pdf_string(text: &str) -> String
+{
+    let mut string = "(".to_string();
+    for character in text.chars()
+    {
+        match character
+        {
+            '(' | ')' | '\\' => { string.push('\\'); string.push(character); }
+            ' '..='~' => string.push(character),
+            '\u{a0}'..='\u{ff}' => string.push_str(&format!("\\{:03o}", character as u32)),
+            _ => string.push('?')
+        }
+    }
+    string.push(')');
+    string
+}
+
+fn path_operators(shape: &Shape) -> String
+{
+    let mut operators = String::new();
+    for command in shape.path.iter()
+    {
+        match command
+        {
+            PathCommand::MOVE(x, y) => operators.push_str(&format!("{} {} m\n", number(*x), number(*y))),
+            PathCommand::LINE(x, y) => operators.push_str(&format!("{} {} l\n", number(*x), number(*y))),
+            PathCommand::CURVE(x1, y1, x2, y2, x, y) =>
+                operators.push_str(&format!("{} {} {} {} {} {} c\n", number(*x1), number(*y1), number(*x2), number(*y2), number(*x), number(*y))),
+            PathCommand::CLOSE => operators.push_str("h\n")
+        }
+    }
+    operators
+}
+
+fn page_content(drawing: &Drawing, fit_to_content: bool, margin: f32, page: &PageSetup) -> (String, f32, f32)
+{
+    let viewport = viewport(drawing, fit_to_content, margin);
+    let layout = page.layout(&viewport);
+    let [a, b, c, d, e, f] = layout.matrix;
+    let mut content = format!("{} {} {} {} {} {} cm\n{} {} {} {} re W n\n1 J 1 j\n", number(a), number(b), number(c), number(d), number(e), number(f),
+                              number(viewport.min_x), number(viewport.min_y), number(viewport.width()), number(viewport.height()));
+    for item in vector_items(drawing, &viewport, !fit_to_content)
+    {
+        match item
+        {
+            VectorItem::SHAPE(shape) =>
+            {
+                content.push_str("q\n");
+                if let Some((red, green, blue)) = shape.fill
+                {
+                    content.push_str(&format!("{} {} {} rg\n", number(red), number(green), number(blue)));
+                    if shape.fill_opacity < 1.0
+                    {
+                        content.push_str("/Translucent gs\n");
+                    }
+                }
+                if let Some(pen) = &shape.pen
+                {
+                    // Difference with white inverts the colours underneath
+                    let (red, green, blue) = if pen.reverse { (1.0, 1.0, 1.0) } else { pen.color };
+                    if pen.reverse
+                    {
+                        content.push_str("/Reverse gs\n");
+                    }
+                    content.push_str(&format!("{} {} {} RG\n{} w\n", number(red), number(green), number(blue), number(pen.width)));
+                }
+                content.push_str(&path_operators(&shape));
+                let painting = match (shape.fill.is_some(), shape.pen.is_some())
+                {
+                    (true, true) => "B",
+                    (true, false) => "f",
+                    (false, true) => "S",
+                    (false, false) => "n"
+                };
+                content.push_str(&format!("{painting}\nQ\n"));
+            }
+            VectorItem::TEXT(text) =>
+            {
+                // The text matrix flips the glyphs back upright in the canvas coordinates
+                let (sin, cos) = text.rotation_angle.to_radians().sin_cos();
+                let size = text.font_size as f32;
+                let (red, green, blue) = color(&text.color);
+                content.push_str(&format!("BT\n{} {} {} rg\n/Label 1 Tf\n{} {} {} {} {} {} Tm\n{} Tj\nET\n",
+                                          number(red), number(green), number(blue), number(cos * size), number(sin * size), number(sin * size), number(-cos * size),
+                                          number(text.x), number(text.y), pdf_string(&text.text)));
+            }
+        }
+    }
+    (content, layout.page_width, layout.page_height)
+}
+
+impl Renderer for PdfRenderer
+{
+    fn render(&self, drawing: &Drawing) -> Vec<u8>
+    {
+        let fit_to_content = self.fit_to_content && drawing.get_bounding_box().is_some();
+        let (content, page_width, page_height) = page_content(drawing, fit_to_content, self.margin, &self.page);
+
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            format!("<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Contents 4 0 R /Resources << /Font << /Label 5 0 R >> /ExtGState << /Reverse 6 0 R /Translucent 7 0 R >> >> >>",
+                    number(page_width), number(page_height)),
+            format!("<< /Length {} >>\nstream\n{}endstream", content.len(), content),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_string(),
+            "<< /Type /ExtGState /BM /Difference >>".to_string(),
+            "<< /Type /ExtGState /ca 0.5 >>".to_string()
+        ];
+
+        let mut pdf = "%PDF-1.4\n".to_string();
+        let mut offsets: Vec<usize> = vec!();
+        for (index, object) in objects.iter().enumerate()
+        {
+            offsets.push(pdf.len());
+            pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", index + 1, object));
+        }
+        let xref_offset = pdf.len();
+        pdf.push_str(&format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1));
+        for offset in offsets
+        {
+            pdf.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        pdf.push_str(&format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref_offset));
+        pdf.into_bytes()
+    }
+}
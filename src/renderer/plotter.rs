@@ -0,0 +1,105 @@
+use crate::drawing::{ BoundingBox, Drawing, DrawingElement, PenMode };
+use crate::renderer::stroke_font;
+
+const ARC_STEP: f32 = 5.0; // degrees between the points of an arc
+
+// Paths the pen draws without lifting, in millimetres with the y axis pointing up as on a plotter bed
+pub type Stroke = Vec<(f32, f32)>;
+
+// Pen-down runs of the drawing in canvas coordinates. A plotter has one pen and can't fill or erase,
+// so fills become outlines, erasing lines are left out and the colours are ignored
+fn drawing_strokes(drawing: &Drawing) -> Vec<Stroke>
+{
+    let mut strokes: Vec<Stroke> = vec!();
+    for element in drawing.get_elements()
+    {
+        match element
+        {
+            DrawingElement::SEGMENT(segment) =>
+            {
+                if segment.style.pen_mode == PenMode::ERASE || (segment.x1 == segment.x2 && segment.y1 == segment.y2) { continue; }
+                match strokes.last_mut()
+                {
+                    Some(stroke) if stroke.last() == Some(&(segment.x1, segment.y1)) => stroke.push((segment.x2, segment.y2)),
+                    _ => strokes.push(vec!((segment.x1, segment.y1), (segment.x2, segment.y2)))
+                }
+            }
+            DrawingElement::POLYGON(polygon) =>
+            {
+                let mut outline = polygon.points.clone();
+                outline.push(polygon.points[0]);
+                strokes.push(outline);
+            }
+            DrawingElement::ARC(arc) =>
+            {
+                if arc.style.pen_mode == PenMode::ERASE { continue; }
+                let sweep_angle = arc.sweep_angle.clamp(-360.0, 360.0);
+                let steps = (sweep_angle.abs() / ARC_STEP).ceil().max(1.0) as usize;
+                strokes.push((0..=steps).map(|step| arc.point_at(arc.start_angle + sweep_angle * step as f32 / steps as f32)).collect());
+            }
+            DrawingElement::TEXT(text) => strokes.extend(stroke_font::text_strokes(text))
+        }
+    }
+    strokes
+}
+
+fn distance((x1, y1): (f32, f32), (x2, y2): (f32, f32)) -> f32
+{
+    (x2 - x1).hypot(y2 - y1)
+}
+
+// Distance covered with the pen up going through the strokes in order, starting and ending at the origin
+pub fn travel_distance(strokes: &[Stroke]) -> f32
+{
+    let mut position = (0.0, 0.0);
+    let mut travel = 0.0;
+    for stroke in strokes
+    {
+        travel += distance(position, stroke[0]);
+        position = *stroke.last().unwrap();
+    }
+    travel + distance(position, (0.0, 0.0))
+}
+
+// Greedily draws the closest stroke next, from whichever of its ends is nearer
+fn order_strokes(mut strokes: Vec<Stroke>) -> Vec<Stroke>
+{
+    let mut ordered: Vec<Stroke> = Vec::with_capacity(strokes.len());
+    let mut position = (0.0, 0.0);
+    while !strokes.is_empty()
+    {
+        let (mut closest_index, mut closest_distance, mut reverse) = (0, f32::MAX, false);
+        for (index, stroke) in strokes.iter().enumerate()
+        {
+            let (start_distance, end_distance) = (distance(position, stroke[0]), distance(position, *stroke.last().unwrap()));
+            if start_distance < closest_distance
+            {
+                (closest_index, closest_distance, reverse) = (index, start_distance, false);
+            }
+            if end_distance < closest_distance
+            {
+                (closest_index, closest_distance, reverse) = (index, end_distance, true);
+            }
+        }
+        let mut stroke = strokes.swap_remove(closest_index);
+        if reverse
+        {
+            stroke.reverse();
+        }
+        position = *stroke.last().unwrap();
+        ordered.push(stroke);
+    }
+    ordered
+}
+
+// Strokes of the part of the drawing inside the viewport, in the order the pen should draw them
+pub fn plot_strokes(drawing: &Drawing, viewport: &BoundingBox, millimetres_per_unit: f32, optimize_travel: bool) -> Vec<Stroke>
+{
+    let strokes: Vec<Stroke> = drawing_strokes(drawing).into_iter().map(|stroke| stroke.into_iter()
+        .map(|(x, y)| ((x - viewport.min_x) * millimetres_per_unit, (viewport.max_y - y) * millimetres_per_unit)).collect()).collect();
+    if !optimize_travel
+    {
+        return strokes;
+    }
+    order_strokes(strokes)
+}
@@ -0,0 +1,40 @@
+use crate::drawing::Drawing;
+use crate::renderer::{ raster, viewport, Renderer };
+
+const CANVAS_DPI: f32 = 96.0; // canvas units are CSS pixels
+const INCHES_PER_METER: f32 = 39.3701;
+
+pub struct PngRenderer
+{
+    pub fit_to_content: bool,  // crop the image to the drawing instead of the whole canvas
+    pub margin: f32,           // space left around the drawing when fitting to content
+    pub dpi: f32               // resolution of the image, at 96 one pixel per canvas unit
+}
+impl Default for PngRenderer
+{
+    fn default() -> Self
+    {
+        Self { fit_to_content: false, margin: 10.0, dpi: CANVAS_DPI }
+    }
+}
+
+impl Renderer for PngRenderer
+{
+    fn render(&self, drawing: &Drawing) -> Vec<u8>
+    {
+        let fit_to_content = self.fit_to_content && drawing.get_bounding_box().is_some();
+        let viewport = viewport(drawing, fit_to_content, self.margin);
+        let raster = raster::rasterize(drawing, &viewport, self.dpi / CANVAS_DPI, !fit_to_content);
+
+        let mut png_data: Vec<u8> = vec!();
+        let mut encoder = png::Encoder::new(&mut png_data, raster.width as u32, raster.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let pixels_per_meter = (self.dpi * INCHES_PER_METER).round() as u32;
+        encoder.set_pixel_dims(Some(png::PixelDimensions { xppu: pixels_per_meter, yppu: pixels_per_meter, unit: png::Unit::Meter }));
+        let mut writer = encoder.write_header().expect("Unable to encode the PNG header");
+        writer.write_image_data(&raster.to_rgb8()).expect("Unable to encode the PNG image");
+        writer.finish().expect("Unable to encode the PNG image");
+        png_data
+    }
+}
@@ -0,0 +1,104 @@
+use crate::drawing::Drawing;
+use crate::renderer::{ parse_color, raster, viewport, Renderer };
+
+const INK_THRESHOLD: i32 = 48; // how far from the background a pixel has to be to raise a braille dot
+
+// Text with ANSI colours showing a rough picture of the drawing in a terminal
+pub struct PreviewRenderer
+{
+    pub columns: usize,        // width of the picture in characters
+    pub braille: bool,         // 2x4 dots per character in the colour of the lines instead of two coloured blocks
+    pub fit_to_content: bool,  // show the drawing instead of the whole canvas
+    pub margin: f32            // space left around the drawing when fitting to content
+}
+impl Default for PreviewRenderer
+{
+    fn default() -> Self
+    {
+        Self { columns: 80, braille: false, fit_to_content: true, margin: 10.0 }
+    }
+}
+
+fn difference(first: &[u8], second: &[u8]) -> i32
+{
+    first.iter().zip(second).map(|(a, b)| (*a as i32 - *b as i32).abs()).sum()
+}
+
+impl PreviewRenderer
+{
+    // Every character shows two pixels, the upper half block in the foreground colour and the lower in the background colour
+    fn half_blocks(rgb: &[u8], width: usize, height: usize) -> String
+    {
+        let pixel = |x: usize, y: usize| &rgb[(y.min(height - 1) * width + x) * 3..][..3];
+        let mut preview = String::new();
+        for row in (0..height).step_by(2)
+        {
+            for x in 0..width
+            {
+                let (top, bottom) = (pixel(x, row), pixel(x, row + 1));
+                preview.push_str(&format!("\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m\u{2580}", top[0], top[1], top[2], bottom[0], bottom[1], bottom[2]));
+            }
+            preview.push_str("\x1b[0m\n");
+        }
+        preview
+    }
+
+    // Dots are raised where the picture differs from the background, coloured like the strongest of them
+    fn braille(rgb: &[u8], width: usize, height: usize, background: &[u8]) -> String
+    {
+        const DOTS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+        let mut preview = String::new();
+        for row in (0..height).step_by(4)
+        {
+            for column in (0..width).step_by(2)
+            {
+                let (mut pattern, mut strongest, mut strongest_difference) = (0, background, 0);
+                for (dot_x, dot_column) in DOTS.iter().enumerate()
+                {
+                    for (dot_y, dot) in dot_column.iter().enumerate()
+                    {
+                        let (x, y) = (column + dot_x, row + dot_y);
+                        if x >= width || y >= height { continue; }
+                        let pixel = &rgb[(y * width + x) * 3..][..3];
+                        let pixel_difference = difference(pixel, background);
+                        if pixel_difference > INK_THRESHOLD
+                        {
+                            pattern |= dot;
+                        }
+                        if pixel_difference > strongest_difference
+                        {
+                            (strongest, strongest_difference) = (pixel, pixel_difference);
+                        }
+                    }
+                }
+                let character = char::from_u32(0x2800 + pattern).unwrap();
+                preview.push_str(&format!("\x1b[38;2;{};{};{}m{}", strongest[0], strongest[1], strongest[2], character));
+            }
+            preview.push_str("\x1b[0m\n");
+        }
+        preview
+    }
+}
+
+impl Renderer for PreviewRenderer
+{
+    fn render(&self, drawing: &Drawing) -> Vec<u8>
+    {
+        let fit_to_content = self.fit_to_content && drawing.get_bounding_box().is_some();
+        let viewport = viewport(drawing, fit_to_content, self.margin);
+        let pixels_per_column = if self.braille { 2.0 } else { 1.0 };
+        let scale = self.columns.max(1) as f32 * pixels_per_column / viewport.width();
+        let raster = raster::rasterize(drawing, &viewport, scale, !fit_to_content);
+        let rgb = raster.to_rgb8();
+        let preview = if self.braille
+        {
+            let (red, green, blue) = parse_color(&drawing.background_color).unwrap_or((0, 0, 0));
+            PreviewRenderer::braille(&rgb, raster.width, raster.height, &[red, green, blue])
+        }
+        else
+        {
+            PreviewRenderer::half_blocks(&rgb, raster.width, raster.height)
+        };
+        preview.into_bytes()
+    }
+}
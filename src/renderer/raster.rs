@@ -0,0 +1,387 @@
+use crate::drawing::{ Arc, BoundingBox, Drawing, DrawingElement, PenMode, Style };
+use crate::renderer::{ parse_color, sprite_outlines };
+use crate::renderer::stroke_font;
+
+const BORDER_WIDTH: f32 = 10.0;     // same border as the SVG output of the whole canvas
+const SUBSCANLINES: usize = 4;      // vertical samples per pixel row when filling polygons
+const SPRITE_OPACITY: f32 = 0.5;
+const SNAP_STEPS: f32 = 256.0;      // positions of the ends of lines per pixel
+
+#[derive(Clone, Copy)]
+enum Paint
+{
+    COLOR([f32; 3]),
+    REVERSE  // inverts the pixels underneath
+}
+
+// Pixels of a shape with how much of each one it covers, from 0 to 1. A pixel can be listed more than once
+type Coverage = Vec<(usize, f32)>;
+
+// RGB image with components between 0 and 255
+#[derive(Clone)]
+pub struct Raster
+{
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<[f32; 3]>
+}
+impl Raster
+{
+    fn new(width: usize, height: usize, background: [f32; 3]) -> Self
+    {
+        Self { width, height, pixels: vec![background; width * height] }
+    }
+
+    fn paint_pixel(&mut self, index: usize, paint: Paint, alpha: f32)
+    {
+        let pixel = &mut self.pixels[index];
+        match paint
+        {
+            Paint::COLOR(color) =>
+            {
+                for component in 0..3
+                {
+                    pixel[component] += (color[component] - pixel[component]) * alpha;
+                }
+            }
+            // Whole pixels are inverted without anti-aliasing, so that inverting them again gives back the same image
+            Paint::REVERSE =>
+            {
+                if alpha >= 0.5
+                {
+                    pixel.iter_mut().for_each(|component| *component = 255.0 - component.round());
+                }
+            }
+        }
+    }
+
+    pub fn to_rgb8(&self) -> Vec<u8>
+    {
+        self.pixels.iter().flat_map(|pixel| pixel.iter().map(|component| component.round().clamp(0.0, 255.0) as u8)).collect()
+    }
+}
+
+fn color_components(color: &str) -> [f32; 3]
+{
+    let (red, green, blue) = parse_color(color).unwrap_or((0, 0, 0));
+    [red as f32, green as f32, blue as f32]
+}
+
+struct Rasterizer
+{
+    raster: Raster,
+    origin_x: f32,
+    origin_y: f32,
+    scale: f32,
+    background: [f32; 3]
+}
+impl Rasterizer
+{
+    fn to_pixel(&self, (x, y): (f32, f32)) -> (f32, f32)
+    {
+        ((x - self.origin_x) * self.scale, (y - self.origin_y) * self.scale)
+    }
+
+    fn paint_of(&self, style: &Style) -> Paint
+    {
+        match style.pen_mode
+        {
+            PenMode::PAINT => Paint::COLOR(color_components(&style.stroke_color)),
+            PenMode::ERASE => Paint::COLOR(self.background),
+            PenMode::REVERSE => Paint::REVERSE
+        }
+    }
+
+    // Range of pixels touched by a box in pixel coordinates, None when it is outside of the image
+    fn pixel_range(&self, min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> Option<(usize, usize, usize, usize)>
+    {
+        let (start_x, start_y) = (min_x.floor().max(0.0), min_y.floor().max(0.0));
+        let (end_x, end_y) = (max_x.ceil().min(self.raster.width as f32), max_y.ceil().min(self.raster.height as f32));
+        if start_x >= end_x || start_y >= end_y { return None; }
+        Some((start_x as usize, start_y as usize, end_x as usize, end_y as usize))
+    }
+
+    // Covers a line with round caps, the coverage of a pixel comes from its distance to the line
+    fn cover_line(&self, start: (f32, f32), end: (f32, f32), width: f32, coverage: &mut Coverage)
+    {
+        // Ends are snapped to a fine grid so that a line drawn back over another one covers exactly the same
+        // pixels, even when the turtle position picked up rounding errors on the way
+        let snap = |(x, y): (f32, f32)| ((x * SNAP_STEPS).round() / SNAP_STEPS, (y * SNAP_STEPS).round() / SNAP_STEPS);
+        let ((x1, y1), (x2, y2)) = (snap(self.to_pixel(start)), snap(self.to_pixel(end)));
+        let half_width = width * self.scale / 2.0;
+        let reach = half_width + 1.0;
+        let Some((start_x, start_y, end_x, end_y)) = self.pixel_range(x1.min(x2) - reach, y1.min(y2) - reach, x1.max(x2) + reach, y1.max(y2) + reach)
+        else { return; };
+
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        let length_squared = dx * dx + dy * dy;
+        for y in start_y..end_y
+        {
+            for x in start_x..end_x
+            {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let t = if length_squared > 0.0 { (((px - x1) * dx + (py - y1) * dy) / length_squared).clamp(0.0, 1.0) } else { 0.0 };
+                let distance = (px - x1 - t * dx).hypot(py - y1 - t * dy);
+                let pixel_coverage = (half_width + 0.5 - distance).clamp(0.0, 1.0);
+                if pixel_coverage > 0.0
+                {
+                    coverage.push((y * self.raster.width + x, pixel_coverage));
+                }
+            }
+        }
+    }
+
+    fn cover_arc(&self, arc: &Arc, coverage: &mut Coverage)
+    {
+        let (center_x, center_y) = self.to_pixel((arc.center_x, arc.center_y));
+        let radius = arc.radius * self.scale;
+        let half_width = arc.style.stroke_width * self.scale / 2.0;
+        let reach = radius + half_width + 1.0;
+        let Some((start_x, start_y, end_x, end_y)) = self.pixel_range(center_x - reach, center_y - reach, center_x + reach, center_y + reach)
+        else { return; };
+
+        let full_circle = arc.sweep_angle.abs() >= 360.0;
+        let first_angle = arc.start_angle.min(arc.start_angle + arc.sweep_angle);
+        let ends = [self.to_pixel(arc.point_at(arc.start_angle)), self.to_pixel(arc.point_at(arc.start_angle + arc.sweep_angle))];
+        for y in start_y..end_y
+        {
+            for x in start_x..end_x
+            {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let angle = (py - center_y).atan2(px - center_x).to_degrees();
+                let distance = if full_circle || (angle - first_angle).rem_euclid(360.0) <= arc.sweep_angle.abs()
+                {
+                    ((px - center_x).hypot(py - center_y) - radius).abs()
+                }
+                else
+                {
+                    ends.iter().map(|(end_x, end_y)| (px - end_x).hypot(py - end_y)).fold(f32::MAX, f32::min)
+                };
+                let pixel_coverage = (half_width + 0.5 - distance).clamp(0.0, 1.0);
+                if pixel_coverage > 0.0
+                {
+                    coverage.push((y * self.raster.width + x, pixel_coverage));
+                }
+            }
+        }
+    }
+
+    // Fills with the nonzero rule, sampling a few lines per pixel row with exact horizontal coverage
+    fn cover_polygon(&self, points: &[(f32, f32)], opacity: f32, coverage: &mut Coverage)
+    {
+        if points.len() < 3 { return; }
+        let points: Vec<(f32, f32)> = points.iter().map(|point| self.to_pixel(*point)).collect();
+        let (min_x, max_x) = points.iter().fold((f32::MAX, f32::MIN), |(min, max), (x, _)| (min.min(*x), max.max(*x)));
+        let (min_y, max_y) = points.iter().fold((f32::MAX, f32::MIN), |(min, max), (_, y)| (min.min(*y), max.max(*y)));
+        let Some((start_x, start_y, end_x, end_y)) = self.pixel_range(min_x, min_y, max_x, max_y)
+        else { return; };
+
+        let mut row_coverage = vec![0.0f32; end_x - start_x];
+        let mut crossings: Vec<(f32, i32)> = vec!();
+        for y in start_y..end_y
+        {
+            row_coverage.iter_mut().for_each(|value| *value = 0.0);
+            for subscanline in 0..SUBSCANLINES
+            {
+                let sample_y = y as f32 + (subscanline as f32 + 0.5) / SUBSCANLINES as f32;
+                crossings.clear();
+                for (index, &(x1, y1)) in points.iter().enumerate()
+                {
+                    let (x2, y2) = points[(index + 1) % points.len()];
+                    if (y1 <= sample_y) != (y2 <= sample_y)
+                    {
+                        let x = x1 + (sample_y - y1) / (y2 - y1) * (x2 - x1);
+                        crossings.push((x, if y2 > y1 { 1 } else { -1 }));
+                    }
+                }
+                crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+                let mut winding = 0;
+                for pair in crossings.windows(2)
+                {
+                    winding += pair[0].1;
+                    if winding == 0 { continue; }
+                    let (span_start, span_end) = (pair[0].0.max(start_x as f32), pair[1].0.min(end_x as f32));
+                    let mut x = span_start.floor().max(start_x as f32) as usize;
+                    while (x as f32) < span_end && x < end_x
+                    {
+                        let overlap = span_end.min(x as f32 + 1.0) - span_start.max(x as f32);
+                        row_coverage[x - start_x] += overlap.max(0.0) / SUBSCANLINES as f32;
+                        x += 1;
+                    }
+                }
+            }
+            for x in start_x..end_x
+            {
+                let alpha = row_coverage[x - start_x].min(1.0) * opacity;
+                if alpha > 0.0
+                {
+                    coverage.push((y * self.raster.width + x, alpha));
+                }
+            }
+        }
+    }
+
+    fn cover_polyline(&self, points: &[(f32, f32)], width: f32, coverage: &mut Coverage)
+    {
+        for pair in points.windows(2)
+        {
+            self.cover_line(pair[0], pair[1], width, coverage);
+        }
+    }
+
+    // Paints every pixel of a shape once with its highest coverage, the joints of a polyline are not painted twice
+    fn paint(&mut self, mut coverage: Coverage, paint: Paint)
+    {
+        coverage.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.total_cmp(&a.1)));
+        coverage.dedup_by_key(|(index, _)| *index);
+        for (index, alpha) in coverage
+        {
+            self.raster.paint_pixel(index, paint, alpha);
+        }
+    }
+
+    fn fill_polygon(&mut self, points: &[(f32, f32)], paint: Paint, opacity: f32)
+    {
+        let mut coverage = vec!();
+        self.cover_polygon(points, opacity, &mut coverage);
+        self.paint(coverage, paint);
+    }
+
+    fn stroke_polyline(&mut self, points: &[(f32, f32)], width: f32, paint: Paint)
+    {
+        let mut coverage = vec!();
+        self.cover_polyline(points, width, &mut coverage);
+        self.paint(coverage, paint);
+    }
+}
+
+impl Rasterizer
+{
+    fn new(drawing: &Drawing, viewport: &BoundingBox, scale: f32) -> Self
+    {
+        let (width, height) = raster_size(viewport, scale);
+        let background = color_components(&drawing.background_color);
+        Self { raster: Raster::new(width, height, background), origin_x: viewport.min_x, origin_y: viewport.min_y, scale, background }
+    }
+
+    fn paint_element(&mut self, element: &DrawingElement)
+    {
+        match element
+        {
+            DrawingElement::SEGMENT(segment) =>
+            {
+                let paint = self.paint_of(&segment.style);
+                self.stroke_polyline(&[(segment.x1, segment.y1), (segment.x2, segment.y2)], segment.style.stroke_width, paint);
+            }
+            DrawingElement::POLYGON(polygon) =>
+            {
+                if let Some(fill_color) = &polygon.style.fill_color
+                {
+                    self.fill_polygon(&polygon.points, Paint::COLOR(color_components(fill_color)), 1.0);
+                }
+                if polygon.style.stroke_width > 0.0
+                {
+                    let paint = self.paint_of(&polygon.style);
+                    let mut outline = polygon.points.clone();
+                    outline.push(polygon.points[0]);
+                    self.stroke_polyline(&outline, polygon.style.stroke_width, paint);
+                }
+            }
+            DrawingElement::ARC(arc) =>
+            {
+                if let Some(fill_color) = &arc.style.fill_color
+                {
+                    let steps = (arc.sweep_angle.abs().min(360.0) / 5.0).ceil().max(1.0) as usize;
+                    let outline: Vec<(f32, f32)> = (0..=steps).map(|step| arc.point_at(arc.start_angle + arc.sweep_angle * step as f32 / steps as f32)).collect();
+                    self.fill_polygon(&outline, Paint::COLOR(color_components(fill_color)), 1.0);
+                }
+                if arc.style.stroke_width > 0.0
+                {
+                    let mut coverage = vec!();
+                    self.cover_arc(arc, &mut coverage);
+                    let paint = self.paint_of(&arc.style);
+                    self.paint(coverage, paint);
+                }
+            }
+            DrawingElement::TEXT(text) =>
+            {
+                let mut coverage = vec!();
+                for stroke in stroke_font::text_strokes(text)
+                {
+                    self.cover_polyline(&stroke, stroke_font::stroke_width(text.font_size), &mut coverage);
+                }
+                self.paint(coverage, Paint::COLOR(color_components(&text.color)));
+            }
+        }
+    }
+
+    fn paint_sprites(&mut self, drawing: &Drawing)
+    {
+        for sprite in drawing.get_sprites()
+        {
+            let paint = Paint::COLOR(color_components(&sprite.color));
+            for mut outline in sprite_outlines(sprite)
+            {
+                self.fill_polygon(&outline, paint, SPRITE_OPACITY);
+                outline.push(outline[0]);
+                self.stroke_polyline(&outline, 1.0, paint);
+            }
+        }
+    }
+
+    // The image with the border around the canvas when it is drawn
+    fn finished_raster(&self, drawing: &Drawing, draw_border: bool) -> Raster
+    {
+        let mut finished = Rasterizer { raster: self.raster.clone(), ..*self };
+        if draw_border
+        {
+            let (width, height) = (drawing.width as f32, drawing.height as f32);
+            let corners = [(0.0, 0.0), (width, 0.0), (width, height), (0.0, height), (0.0, 0.0)];
+            finished.stroke_polyline(&corners, BORDER_WIDTH, Paint::COLOR([0.0, 0.0, 0.0]));
+        }
+        finished.raster
+    }
+}
+
+// Width and height in pixels of the image of the viewport
+pub fn raster_size(viewport: &BoundingBox, scale: f32) -> (usize, usize)
+{
+    ((viewport.width() * scale).ceil().max(1.0) as usize, (viewport.height() * scale).ceil().max(1.0) as usize)
+}
+
+// Paints the part of the drawing inside the viewport, scale is the number of pixels per canvas unit
+pub fn rasterize(drawing: &Drawing, viewport: &BoundingBox, scale: f32, draw_border: bool) -> Raster
+{
+    let mut rasterizer = Rasterizer::new(drawing, viewport, scale);
+    for element in drawing.get_elements()
+    {
+        rasterizer.paint_element(element);
+    }
+    rasterizer.paint_sprites(drawing);
+    rasterizer.finished_raster(drawing, draw_border)
+}
+
+// Paints the drawing as it looks at every tick something is drawn at, each frame is passed along with the
+// number of ticks it lasts. The last frame has the turtles on top and lasts 0 ticks
+#[cfg(feature = "gif")]
+pub fn rasterize_frames(drawing: &Drawing, viewport: &BoundingBox, scale: f32, draw_border: bool, mut add_frame: impl FnMut(Raster, u32))
+{
+    let mut rasterizer = Rasterizer::new(drawing, viewport, scale);
+    let mut frame_tick = 0;
+    for (element, tick) in drawing.get_elements().iter().zip(drawing.get_element_ticks())
+    {
+        if *tick > frame_tick
+        {
+            add_frame(rasterizer.finished_raster(drawing, draw_border), tick - frame_tick);
+            frame_tick = *tick;
+        }
+        rasterizer.paint_element(element);
+    }
+    if drawing.get_duration() > frame_tick
+    {
+        add_frame(rasterizer.finished_raster(drawing, draw_border), drawing.get_duration() - frame_tick);
+    }
+    rasterizer.paint_sprites(drawing);
+    add_frame(rasterizer.finished_raster(drawing, draw_border), 0);
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use lazy_static::lazy_static;
+
+use crate::drawing::Text;
+
+// Glyphs are drawn on a grid 4 units wide and 6 units tall with y pointing up from the baseline
+const GLYPH_HEIGHT: f32 = 6.0;
+const GLYPH_ADVANCE: f32 = 5.0;
+const CAP_HEIGHT: f32 = 0.7; // relative to the font size
+
+lazy_static!
+{
+    // Each glyph is a list of strokes separated by ';', each stroke a list of x,y points
+    static ref GLYPHS: HashMap<char, Vec<Vec<(f32, f32)>>> =
+    {
+        let glyphs = [
+            ('A', "0,0 0,4 2,6 4,4 4,0; 0,3 4,3"),
+            ('B', "0,0 0,6 3,6 4,5 4,4 3,3 0,3; 3,3 4,2 4,1 3,0 0,0"),
+            ('C', "4,5 3,6 1,6 0,5 0,1 1,0 3,0 4,1"),
+            ('D', "0,0 0,6 2,6 4,4 4,2 2,0 0,0"),
+            ('E', "4,6 0,6 0,0 4,0; 0,3 3,3"),
+            ('F', "4,6 0,6 0,0; 0,3 3,3"),
+            ('G', "4,5 3,6 1,6 0,5 0,1 1,0 3,0 4,1 4,3 2,3"),
+            ('H', "0,0 0,6; 4,0 4,6; 0,3 4,3"),
+            ('I', "1,6 3,6; 2,6 2,0; 1,0 3,0"),
+            ('J', "4,6 4,1 3,0 1,0 0,1"),
+            ('K', "0,0 0,6; 4,6 0,2; 1,3 4,0"),
+            ('L', "0,6 0,0 4,0"),
+            ('M', "0,0 0,6 2,3 4,6 4,0"),
+            ('N', "0,0 0,6 4,0 4,6"),
+            ('O', "1,0 0,1 0,5 1,6 3,6 4,5 4,1 3,0 1,0"),
+            ('P', "0,0 0,6 3,6 4,5 4,4 3,3 0,3"),
+            ('Q', "1,0 0,1 0,5 1,6 3,6 4,5 4,1 3,0 1,0; 2,2 4,0"),
+            ('R', "0,0 0,6 3,6 4,5 4,4 3,3 0,3; 2,3 4,0"),
+            ('S', "4,5 3,6 1,6 0,5 0,4 1,3 3,3 4,2 4,1 3,0 1,0 0,1"),
+            ('T', "0,6 4,6; 2,6 2,0"),
+            ('U', "0,6 0,1 1,0 3,0 4,1 4,6"),
+            ('V', "0,6 2,0 4,6"),
+            ('W', "0,6 1,0 2,3 3,0 4,6"),
+            ('X', "0,0 4,6; 0,6 4,0"),
+            ('Y', "0,6 2,3 4,6; 2,3 2,0"),
+            ('Z', "0,6 4,6 0,0 4,0"),
+            ('0', "1,0 0,1 0,5 1,6 3,6 4,5 4,1 3,0 1,0; 0,1 4,5"),
+            ('1', "1,5 2,6 2,0; 1,0 3,0"),
+            ('2', "0,5 1,6 3,6 4,5 4,4 0,0 4,0"),
+            ('3', "0,5 1,6 3,6 4,5 4,4 3,3 4,2 4,1 3,0 1,0 0,1; 1,3 3,3"),
+            ('4', "3,0 3,6 0,2 4,2"),
+            ('5', "4,6 0,6 0,3 3,3 4,2 4,1 3,0 0,0"),
+            ('6', "4,5 3,6 1,6 0,5 0,1 1,0 3,0 4,1 4,2 3,3 0,3"),
+            ('7', "0,6 4,6 1,0"),
+            ('8', "1,3 0,4 0,5 1,6 3,6 4,5 4,4 3,3 1,3 0,2 0,1 1,0 3,0 4,1 4,2 3,3"),
+            ('9', "4,3 1,3 0,4 0,5 1,6 3,6 4,5 4,1 3,0 1,0 0,1"),
+            ('.', "2,0 2,0.4"),
+            (',', "2,0.5 1,-1"),
+            ('!', "2,6 2,2; 2,0 2,0.4"),
+            ('?', "0,5 1,6 3,6 4,5 4,4 2,3 2,2; 2,0 2,0.4"),
+            (':', "2,4 2,4.4; 2,1 2,1.4"),
+            ('-', "1,3 3,3"),
+            ('+', "1,3 3,3; 2,2 2,4"),
+            ('=', "1,2 3,2; 1,4 3,4"),
+            ('*', "2,1 2,5; 0,2 4,4; 0,4 4,2"),
+            ('/', "0,0 4,6"),
+            ('(', "3,6 2,5 2,1 3,0"),
+            (')', "1,6 2,5 2,1 1,0"),
+            ('\'', "2,6 2,5"),
+            ('"', "1,6 1,5; 3,6 3,5"),
+            ('_', "0,0 4,0"),
+            (' ', ""),
+            ('\u{0}', "0,0 0,6 4,6 4,0 0,0") // drawn for characters without a glyph
+        ];
+        let mut map = HashMap::new();
+        for (character, strokes) in glyphs
+        {
+            let strokes = strokes.split(';').map(|stroke| stroke.split_whitespace().map(|point|
+            {
+                let (x, y) = point.split_once(',').unwrap();
+                (x.parse::<f32>().unwrap(), y.parse::<f32>().unwrap())
+            }).collect::<Vec<(f32, f32)>>()).filter(|stroke| !stroke.is_empty()).collect();
+            map.insert(character, strokes);
+        }
+        map
+    };
+}
+
+pub fn stroke_width(font_size: i32) -> f32
+{
+    font_size as f32 / 12.0
+}
+
+// Polylines tracing the text in canvas coordinates, used by outputs that can't embed fonts
+pub fn text_strokes(text: &Text) -> Vec<Vec<(f32, f32)>>
+{
+    let unit = text.font_size as f32 * CAP_HEIGHT / GLYPH_HEIGHT;
+    let (sin, cos) = text.rotation_angle.to_radians().sin_cos();
+    let mut strokes = vec!();
+    for (index, character) in text.text.chars().enumerate()
+    {
+        let glyph = GLYPHS.get(&character.to_ascii_uppercase()).unwrap_or_else(|| GLYPHS.get(&'\u{0}').unwrap());
+        let offset = index as f32 * GLYPH_ADVANCE;
+        for glyph_stroke in glyph
+        {
+            strokes.push(glyph_stroke.iter().map(|(x, y)|
+            {
+                let (dx, dy) = ((offset + x) * unit, -y * unit);
+                (text.x + dx * cos - dy * sin, text.y + dx * sin + dy * cos)
+            }).collect());
+        }
+    }
+    strokes
+}
@@ -0,0 +1,252 @@
+use crate::drawing::{ Drawing, DrawingElement, PenMode, Segment, Style, TICKS_PER_SECOND };
+use crate::renderer::{ viewport, Renderer };
+
+pub struct SvgRenderer
+{
+    pub fit_to_content: bool,     // use a viewBox around the drawing instead of the whole canvas
+    pub margin: f32,              // space left around the drawing when fitting to content
+    pub merge_segments: bool,     // draw connected segments of the same style as one path
+    pub precision: Option<usize>, // decimal places of the coordinates, all of them when None
+    pub animate: bool             // reveal the elements at the time they were drawn, following wait
+}
+impl Default for SvgRenderer
+{
+    fn default() -> Self
+    {
+        Self { fit_to_content: false, margin: 10.0, merge_segments: true, precision: Some(2), animate: false }
+    }
+}
+
+impl SvgRenderer
+{
+    fn style_to_css(style: &Style, background_color: &str) -> String
+    {
+        let stroke = match style.pen_mode
+        {
+            PenMode::PAINT => style.stroke_color.clone(),
+            PenMode::ERASE => background_color.to_string(),
+            PenMode::REVERSE => "white;mix-blend-mode:difference".to_string()
+        };
+        match &style.fill_color
+        {
+            Some(fill_color) => format!("fill:{};stroke:{};stroke-width:{}", fill_color, stroke, style.stroke_width),
+            None => format!("stroke:{};stroke-width:{}", stroke, style.stroke_width)
+        }
+    }
+
+    fn number(&self, value: f32) -> String
+    {
+        match self.precision
+        {
+            Some(precision) =>
+            {
+                let rounded = format!("{:.*}", precision, value);
+                let trimmed = if rounded.contains('.') { rounded.trim_end_matches('0').trim_end_matches('.') } else { rounded.as_str() };
+                if trimmed == "-0" { "0".to_string() } else { trimmed.to_string() }
+            }
+            None => format!("{}", value)
+        }
+    }
+
+    // Number of segments starting at the given one that continue each other with the same style,
+    // reversing segments are never merged as a path would only invert the pixels once
+    fn connected_segments(&self, elements: &[DrawingElement], ticks: &[u32]) -> usize
+    {
+        let mut count = 1;
+        if !self.merge_segments { return count; }
+
+        while let (Some(DrawingElement::SEGMENT(previous)), Some(DrawingElement::SEGMENT(next))) = (elements.get(count - 1), elements.get(count))
+        {
+            if next.style != previous.style || next.style.pen_mode == PenMode::REVERSE || next.x1 != previous.x2 || next.y1 != previous.y2 { break; }
+            if self.animate && ticks[count] != ticks[0] { break; }
+            count += 1;
+        }
+        count
+    }
+
+    // Difference between two coordinates as written, with a precision it is taken between the rounded coordinates
+    // so that the rounding errors of a long path do not add up
+    fn difference(&self, from: f32, to: f32) -> String
+    {
+        match self.precision
+        {
+            Some(precision) =>
+            {
+                let scale = 10f64.powi(precision as i32);
+                let steps = (to as f64 * scale).round() - (from as f64 * scale).round();
+                self.number((steps / scale) as f32)
+            }
+            None => self.number(to - from)
+        }
+    }
+
+    // Connected segments as one path, its moves relative to the previous point.
+    // A minus sign separates the numbers as well as a space does
+    fn write_segments(&self, svg: &mut String, segments: &[&Segment], background_color: &str, timing: Option<(u32, u32)>)
+    {
+        let mut path = format!("M{} {}l", self.number(segments[0].x1), self.number(segments[0].y1));
+        let (mut x, mut y) = (segments[0].x1, segments[0].y1);
+        for segment in segments.iter()
+        {
+            for number in [self.difference(x, segment.x2), self.difference(y, segment.y2)]
+            {
+                if !path.ends_with('l') && !number.starts_with('-') { path.push(' '); }
+                path.push_str(&number);
+            }
+            (x, y) = (segment.x2, segment.y2);
+        }
+        svg.push_str(&format!("\n\t<path d=\"{}\" style=\"fill:none;{}\"", path, SvgRenderer::style_to_css(&segments[0].style, background_color)));
+        self.close_element(svg, "path", timing, true);
+    }
+
+    // Start and length in milliseconds of the reveal of an element drawn at the given tick, None when not animating.
+    // Everything drawn between two waits appears together and lines are traced during the wait that follows
+    fn timing(&self, animation_ticks: &Option<Vec<u32>>, tick: u32) -> Option<(u32, u32)>
+    {
+        let animation_ticks = animation_ticks.as_ref()?;
+        let next_tick = animation_ticks.get(animation_ticks.partition_point(|animation_tick| *animation_tick <= tick)).copied().unwrap_or(tick);
+        Some((SvgRenderer::milliseconds(tick), SvgRenderer::milliseconds(next_tick) - SvgRenderer::milliseconds(tick)))
+    }
+
+    fn milliseconds(tick: u32) -> u32
+    {
+        (tick as f32 * 1000.0 / TICKS_PER_SECOND).round() as u32
+    }
+
+    fn reveal(begin: u32) -> String
+    {
+        format!("<set attributeName=\"visibility\" to=\"visible\" begin=\"{begin}ms\" fill=\"freeze\" />")
+    }
+
+    // Ends the tag of an element, which is hidden until its time when animating
+    fn close_element(&self, svg: &mut String, name: &str, timing: Option<(u32, u32)>, traced: bool)
+    {
+        let Some((begin, duration)) = timing
+        else
+        {
+            svg.push_str(" />");
+            return;
+        };
+        if !traced
+        {
+            svg.push_str(&format!(" visibility=\"hidden\">{}</{name}>", SvgRenderer::reveal(begin)));
+        }
+        else if duration == 0
+        {
+            svg.push_str(&format!(" pathLength=\"1\" stroke-dasharray=\"1\" stroke-dashoffset=\"1\"><set attributeName=\"stroke-dashoffset\" to=\"0\" begin=\"{begin}ms\" fill=\"freeze\" /></{name}>"));
+        }
+        else
+        {
+            svg.push_str(&format!(" pathLength=\"1\" stroke-dasharray=\"1\" stroke-dashoffset=\"1\"><animate attributeName=\"stroke-dashoffset\" from=\"1\" to=\"0\" begin=\"{begin}ms\" dur=\"{duration}ms\" fill=\"freeze\" /></{name}>"));
+        }
+    }
+}
+
+impl Renderer for SvgRenderer
+{
+    fn render(&self, drawing: &Drawing) -> Vec<u8>
+    {
+        let mut svg = match drawing.get_bounding_box().filter(|_| self.fit_to_content)
+        {
+            Some(_) =>
+            {
+                let bounding_box = viewport(drawing, true, self.margin);
+                let (x, y, width, height) = (self.number(bounding_box.min_x), self.number(bounding_box.min_y), self.number(bounding_box.width()), self.number(bounding_box.height()));
+                format!("<svg width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\" xmlns=\"http://www.w3.org/2000/svg\">\n\t<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" style=\"fill:{}\" />",
+                        width, height, x, y, width, height, x, y, width, height, drawing.background_color)
+            }
+            None =>
+            {
+                format!("<svg width=\"{}\" height=\"{}\" xmlns=\"http://www.w3.org/2000/svg\">\n\t<rect width=\"100%\" height=\"100%\" style=\"fill:{};stroke-width:10;stroke:rgb(0,0,0)\" />",
+                        drawing.width, drawing.height, drawing.background_color)
+            }
+        };
+
+        // Times at which something is drawn and the end of the animation
+        let animation_ticks = (self.animate && drawing.get_duration() > 0).then(||
+        {
+            let mut animation_ticks = drawing.get_element_ticks().clone();
+            animation_ticks.push(drawing.get_duration());
+            animation_ticks.sort_unstable();
+            animation_ticks.dedup();
+            animation_ticks
+        });
+
+        let elements = drawing.get_elements();
+        let ticks = drawing.get_element_ticks();
+        let mut element_index = 0;
+        while element_index < elements.len()
+        {
+            let timing = self.timing(&animation_ticks, ticks[element_index]);
+            match &elements[element_index]
+            {
+                DrawingElement::SEGMENT(_) =>
+                {
+                    let count = self.connected_segments(&elements[element_index..], &ticks[element_index..]);
+                    let segments: Vec<&Segment> = elements[element_index..element_index + count].iter()
+                        .filter_map(|element| if let DrawingElement::SEGMENT(segment) = element { Some(segment) } else { None })
+                        .collect();
+                    self.write_segments(&mut svg, &segments, &drawing.background_color, timing);
+                    element_index += count;
+                    continue;
+                }
+                DrawingElement::POLYGON(polygon) =>
+                {
+                    let points: Vec<String> = polygon.points.iter().map(|(x, y)| format!("{},{}", self.number(*x), self.number(*y))).collect();
+                    let fill = if polygon.style.fill_color.is_none() { "fill:none;" } else { "" };
+                    svg.push_str(&format!("\n\t<polygon points=\"{}\" style=\"{}{}\"",
+                                          points.join(" "), fill, SvgRenderer::style_to_css(&polygon.style, &drawing.background_color)));
+                    self.close_element(&mut svg, "polygon", timing, polygon.style.fill_color.is_none());
+                }
+                DrawingElement::ARC(arc) =>
+                {
+                    let fill = if arc.style.fill_color.is_none() { "fill:none;" } else { "" };
+                    let css = SvgRenderer::style_to_css(&arc.style, &drawing.background_color);
+                    if arc.sweep_angle.abs() >= 360.0
+                    {
+                        svg.push_str(&format!("\n\t<circle cx=\"{}\" cy=\"{}\" r=\"{}\" style=\"{}{}\"",
+                                              self.number(arc.center_x), self.number(arc.center_y), self.number(arc.radius), fill, css));
+                        self.close_element(&mut svg, "circle", timing, arc.style.fill_color.is_none());
+                    }
+                    else
+                    {
+                        let (start_x, start_y) = arc.point_at(arc.start_angle);
+                        let (end_x, end_y) = arc.point_at(arc.start_angle + arc.sweep_angle);
+                        let large_arc = if arc.sweep_angle.abs() > 180.0 { 1 } else { 0 };
+                        let clockwise = if arc.sweep_angle > 0.0 { 1 } else { 0 };
+                        svg.push_str(&format!("\n\t<path d=\"M {} {} A {} {} 0 {} {} {} {}\" style=\"{}{}\"",
+                                              self.number(start_x), self.number(start_y), self.number(arc.radius), self.number(arc.radius),
+                                              large_arc, clockwise, self.number(end_x), self.number(end_y), fill, css));
+                        self.close_element(&mut svg, "path", timing, arc.style.fill_color.is_none());
+                    }
+                }
+                DrawingElement::TEXT(text) =>
+                {
+                    let (x, y) = (self.number(text.x), self.number(text.y));
+                    let (visibility, reveal) = match timing
+                    {
+                        Some((begin, _)) => (" visibility=\"hidden\"", SvgRenderer::reveal(begin)),
+                        None => ("", "".to_string())
+                    };
+                    svg.push_str(&format!("\n\t<text x=\"{}\" y=\"{}\" fill=\"{}\" font-size=\"{}\" font-family=\"Arial\" transform=\"rotate({} {},{})\"{}>{}{}</text>",
+                                          x, y, text.color, text.font_size, text.rotation_angle, x, y, visibility, reveal, text.text));
+                }
+            }
+            element_index += 1;
+        }
+
+        for sprite in drawing.get_sprites().iter()
+        {
+            // The turtles show up where they ended when the animation is over
+            let (visibility, reveal) = match animation_ticks
+            {
+                Some(_) => (" visibility=\"hidden\"", SvgRenderer::reveal(SvgRenderer::milliseconds(drawing.get_duration()))),
+                None => ("", "".to_string())
+            };
+            svg.push_str(&format!("\n\t<g transform=\"translate({},{}) rotate({})\" style=\"fill:{};fill-opacity:0.5;stroke:{};stroke-width:1\"{}>{}{}</g>",
+                                  self.number(sprite.x), self.number(sprite.y), self.number(sprite.rotation_angle), sprite.color, sprite.color, visibility, reveal, sprite.shape));
+        }
+
+        format!("{svg}\n</svg>").into_bytes()
+    }
+}
@@ -0,0 +1,235 @@
+use std::str::FromStr;
+
+use crate::drawing::{ Arc, BoundingBox, Drawing, DrawingElement, PenMode, Style, Text };
+use crate::renderer::{ parse_color, sprite_outlines };
+
+const POINTS_PER_PIXEL: f32 = 0.75; // canvas pixels are 1/96 inch, points 1/72 inch
+const BORDER_WIDTH: f32 = 10.0;     // same border as the SVG output of the whole canvas
+const SPRITE_OPACITY: f32 = 0.5;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PageSize
+{
+    A4,
+    LETTER
+}
+impl FromStr for PageSize
+{
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err>
+    {
+        match name.to_lowercase().as_str()
+        {
+            "a4" => Ok(PageSize::A4),
+            "letter" => Ok(PageSize::LETTER),
+            _ => Err(())
+        }
+    }
+}
+
+// Where the picture goes on the printed page
+#[derive(Clone, Copy)]
+pub struct PageSetup
+{
+    pub size: PageSize,
+    pub landscape: bool,
+    pub scale_to_fit: bool,  // enlarge the picture to fill the page inside its margin, it is only shrunk to fit otherwise
+    pub page_margin: f32     // in points
+}
+impl Default for PageSetup
+{
+    fn default() -> Self
+    {
+        Self { size: PageSize::A4, landscape: false, scale_to_fit: false, page_margin: 36.0 }
+    }
+}
+
+pub struct PageLayout
+{
+    pub page_width: f32,
+    pub page_height: f32,
+    pub picture: BoundingBox,  // area of the page covered by the viewport, in points from the bottom left corner
+    pub matrix: [f32; 6]       // from canvas coordinates to the page, flipping the y axis to point up
+}
+
+impl PageSetup
+{
+    // The picture is centred on the page at 96 pixels per inch, unless that doesn't fit or it has to fill the page
+    pub fn layout(&self, viewport: &BoundingBox) -> PageLayout
+    {
+        let (width, height) = match self.size
+        {
+            PageSize::A4 => (595.276, 841.89),
+            PageSize::LETTER => (612.0, 792.0)
+        };
+        let (page_width, page_height) = if self.landscape { (height, width) } else { (width, height) };
+        let fitting_scale = ((page_width - 2.0 * self.page_margin) / viewport.width()).min((page_height - 2.0 * self.page_margin) / viewport.height()).max(0.0);
+        let scale = if self.scale_to_fit { fitting_scale } else { POINTS_PER_PIXEL.min(fitting_scale) };
+        let left = (page_width - viewport.width() * scale) / 2.0;
+        let bottom = (page_height - viewport.height() * scale) / 2.0;
+        let mut picture = BoundingBox::from_point(left, bottom);
+        picture.include_point(left + viewport.width() * scale, bottom + viewport.height() * scale);
+        PageLayout { page_width, page_height, picture, matrix: [scale, 0.0, 0.0, -scale, left - viewport.min_x * scale, bottom + viewport.max_y * scale] }
+    }
+}
+
+pub enum PathCommand
+{
+    MOVE(f32, f32),
+    LINE(f32, f32),
+    CURVE(f32, f32, f32, f32, f32, f32),
+    CLOSE
+}
+
+pub struct Pen
+{
+    pub color: (f32, f32, f32),
+    pub width: f32,
+    pub reverse: bool  // inverts what is underneath, formats without blend modes draw it with the colour
+}
+
+pub struct Shape
+{
+    pub path: Vec<PathCommand>,
+    pub fill: Option<(f32, f32, f32)>,
+    pub fill_opacity: f32,
+    pub pen: Option<Pen>
+}
+
+pub enum VectorItem<'a>
+{
+    SHAPE(Shape),
+    TEXT(&'a Text)
+}
+
+// Components between 0 and 1 as used by PDF and PostScript
+pub fn color(color: &str) -> (f32, f32, f32)
+{
+    let (red, green, blue) = parse_color(color).unwrap_or((0, 0, 0));
+    (red as f32 / 255.0, green as f32 / 255.0, blue as f32 / 255.0)
+}
+
+// Short decimal representation, these formats have no use for more than a thousandth of a point
+pub fn number(value: f32) -> String
+{
+    let rounded = format!("{:.3}", value);
+    let trimmed = rounded.trim_end_matches('0').trim_end_matches('.');
+    if trimmed == "-0" { "0".to_string() } else { trimmed.to_string() }
+}
+
+fn pen(style: &Style, background_color: &str) -> Option<Pen>
+{
+    if style.stroke_width <= 0.0 { return None; }
+    let color = match style.pen_mode
+    {
+        PenMode::ERASE => color(background_color),
+        _ => color(&style.stroke_color)
+    };
+    Some(Pen { color, width: style.stroke_width, reverse: style.pen_mode == PenMode::REVERSE })
+}
+
+fn polyline_path(points: &[(f32, f32)], closed: bool) -> Vec<PathCommand>
+{
+    let mut path = vec!(PathCommand::MOVE(points[0].0, points[0].1));
+    path.extend(points.iter().skip(1).map(|(x, y)| PathCommand::LINE(*x, *y)));
+    if closed
+    {
+        path.push(PathCommand::CLOSE);
+    }
+    path
+}
+
+// Cubic Bézier curves following the arc, one for every quarter turn at most
+fn arc_path(arc: &Arc) -> Vec<PathCommand>
+{
+    let sweep_angle = arc.sweep_angle.clamp(-360.0, 360.0);
+    let pieces = (sweep_angle.abs() / 90.0).ceil().max(1.0) as usize;
+    let piece_angle = sweep_angle / pieces as f32;
+    let handle = 4.0 / 3.0 * (piece_angle.to_radians() / 4.0).tan() * arc.radius;
+
+    let (start_x, start_y) = arc.point_at(arc.start_angle);
+    let mut path = vec!(PathCommand::MOVE(start_x, start_y));
+    for piece in 0..pieces
+    {
+        let (from_angle, to_angle) = (arc.start_angle + piece_angle * piece as f32, arc.start_angle + piece_angle * (piece + 1) as f32);
+        let ((from_x, from_y), (to_x, to_y)) = (arc.point_at(from_angle), arc.point_at(to_angle));
+        let ((from_sin, from_cos), (to_sin, to_cos)) = (from_angle.to_radians().sin_cos(), to_angle.to_radians().sin_cos());
+        path.push(PathCommand::CURVE(from_x - handle * from_sin, from_y + handle * from_cos, to_x + handle * to_sin, to_y - handle * to_cos, to_x, to_y));
+    }
+    path
+}
+
+// The drawing as paths and labels in canvas coordinates, in painting order. Connected lines of the
+// same style become one path, except reversing ones that have to invert the pixels line by line
+pub fn vector_items<'a>(drawing: &'a Drawing, viewport: &BoundingBox, draw_border: bool) -> Vec<VectorItem<'a>>
+{
+    let background = Shape { path: polyline_path(&[(viewport.min_x, viewport.min_y), (viewport.max_x, viewport.min_y), (viewport.max_x, viewport.max_y), (viewport.min_x, viewport.max_y)], true),
+                             fill: Some(color(&drawing.background_color)), fill_opacity: 1.0, pen: None };
+    let mut items = vec!(VectorItem::SHAPE(background));
+    let mut polyline: Option<(Vec<(f32, f32)>, &Style)> = None;
+    for element in drawing.get_elements()
+    {
+        if let DrawingElement::SEGMENT(segment) = element
+        {
+            match &mut polyline
+            {
+                Some((points, style)) if *style == &segment.style && segment.style.pen_mode != PenMode::REVERSE && points.last() == Some(&(segment.x1, segment.y1)) =>
+                {
+                    points.push((segment.x2, segment.y2));
+                    continue;
+                }
+                _ => {}
+            }
+            if let Some((points, style)) = polyline.replace((vec!((segment.x1, segment.y1), (segment.x2, segment.y2)), &segment.style))
+            {
+                items.push(VectorItem::SHAPE(Shape { path: polyline_path(&points, false), fill: None, fill_opacity: 1.0, pen: pen(style, &drawing.background_color) }));
+            }
+            continue;
+        }
+        if let Some((points, style)) = polyline.take()
+        {
+            items.push(VectorItem::SHAPE(Shape { path: polyline_path(&points, false), fill: None, fill_opacity: 1.0, pen: pen(style, &drawing.background_color) }));
+        }
+        match element
+        {
+            DrawingElement::POLYGON(polygon) =>
+            {
+                items.push(VectorItem::SHAPE(Shape { path: polyline_path(&polygon.points, true), fill: polygon.style.fill_color.as_deref().map(color),
+                                                     fill_opacity: 1.0, pen: pen(&polygon.style, &drawing.background_color) }));
+            }
+            DrawingElement::ARC(arc) =>
+            {
+                let mut path = arc_path(arc);
+                if arc.style.fill_color.is_some()
+                {
+                    path.push(PathCommand::CLOSE);
+                }
+                items.push(VectorItem::SHAPE(Shape { path, fill: arc.style.fill_color.as_deref().map(color), fill_opacity: 1.0, pen: pen(&arc.style, &drawing.background_color) }));
+            }
+            DrawingElement::TEXT(text) => items.push(VectorItem::TEXT(text)),
+            DrawingElement::SEGMENT(_) => {}
+        }
+    }
+    if let Some((points, style)) = polyline.take()
+    {
+        items.push(VectorItem::SHAPE(Shape { path: polyline_path(&points, false), fill: None, fill_opacity: 1.0, pen: pen(style, &drawing.background_color) }));
+    }
+
+    for sprite in drawing.get_sprites()
+    {
+        for outline in sprite_outlines(sprite)
+        {
+            items.push(VectorItem::SHAPE(Shape { path: polyline_path(&outline, true), fill: Some(color(&sprite.color)), fill_opacity: SPRITE_OPACITY,
+                                                 pen: Some(Pen { color: color(&sprite.color), width: 1.0, reverse: false }) }));
+        }
+    }
+
+    if draw_border
+    {
+        let (width, height) = (drawing.width as f32, drawing.height as f32);
+        items.push(VectorItem::SHAPE(Shape { path: polyline_path(&[(0.0, 0.0), (width, 0.0), (width, height), (0.0, height)], true), fill: None, fill_opacity: 1.0,
+                                             pen: Some(Pen { color: (0.0, 0.0, 0.0), width: BORDER_WIDTH, reverse: false }) }));
+    }
+    items
+}
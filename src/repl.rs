@@ -0,0 +1,121 @@
+use std::fs;
+use std::io::{ self, BufRead, Write };
+
+use crate::interpreter::Session;
+use crate::logo_manager::{ prepare_code, renderer_for_file, Options };
+use crate::parser::parse_logo_code;
+
+const PROMPT: &str = "? ";
+const CONTINUATION_PROMPT: &str = "> ";  // more lines are needed to close a bracket or a procedure definition
+
+pub const HELP: &str = "Instructions run once their brackets and procedure definitions are closed, the turtles and procedures are kept between them.
+    :save <file>    save the drawing so far, in the format given by the extension
+    :reset          start over with a clean canvas and no procedures
+    :help           print this message
+    :quit           leave, as does the end of the input";
+
+// Procedure definitions of the input, which are parsed again in front of every later input as calls are only
+// recognised after the definition of the procedure, and whether the last definition is still missing its end
+fn procedure_definitions(input: &str) -> (String, bool)
+{
+    let mut definitions = String::new();
+    let mut defining = false;
+    for word in input.split_whitespace()
+    {
+        if word == "to" { defining = true; }
+        if defining
+        {
+            definitions.push_str(word);
+            definitions.push(' ');
+        }
+        if word == "end" { defining = false; }
+    }
+    (definitions, defining)
+}
+
+fn is_complete(input: &str) -> bool
+{
+    let open_brackets = input.chars().fold(0, |depth, character| match character
+    {
+        '[' => depth + 1,
+        ']' => depth - 1,
+        _ => depth
+    });
+    open_brackets <= 0 && !procedure_definitions(input).1
+}
+
+fn save(file_path: &str, session: &Session, options: &Options, output: &mut impl Write, errors: &mut impl Write) -> io::Result<()>
+{
+    let Some(renderer) = renderer_for_file(file_path, options)
+    else
+    {
+        return writeln!(errors, "Unsupported output format, {file_path} not saved");
+    };
+    match fs::write(file_path, renderer.render(&session.drawing()))
+    {
+        Ok(()) => writeln!(output, "Saved {file_path}"),
+        Err(error) => writeln!(errors, "Unable to write to file {file_path}: {error}")
+    }
+}
+
+// Reads Logo from the standard input until its end or :quit
+pub fn run(options: &Options) -> Result<(), String>
+{
+    run_from(options, io::stdin().lock(), &mut io::stdout(), &mut io::stderr()).map(|_| ()).map_err(|error| error.to_string())
+}
+
+// Reads Logo from the input until its end or :quit, printing the prompts and messages to the output and the errors
+// to their own. The session is returned with the turtles and the drawing as the input left them
+pub fn run_from(options: &Options, input_lines: impl BufRead, output: &mut impl Write, errors: &mut impl Write) -> io::Result<Session>
+{
+    let new_session = || Session::new(&options.interpreter).map_err(|error| io::Error::other(format!("Runtime error: {error}")));
+    let mut session = new_session()?;
+    let mut definitions = String::new();
+    let mut input = String::new();
+
+    writeln!(output, "Logo, :help lists the commands")?;
+    let mut lines = input_lines.lines();
+    loop
+    {
+        write!(output, "{}", if input.is_empty() { PROMPT } else { CONTINUATION_PROMPT })?;
+        output.flush()?;
+        let Some(line) = lines.next()
+        else
+        {
+            writeln!(output)?;
+            return Ok(session);
+        };
+        let line = line?;
+
+        if input.is_empty() && line.trim_start().starts_with(':')
+        {
+            let mut words = line.split_whitespace();
+            match (words.next().unwrap_or_default(), words.next())
+            {
+                (":save", Some(file_path)) => save(file_path, &session, options, output, errors)?,
+                (":save", None) => writeln!(errors, "Missing file of :save")?,
+                (":reset", _) =>
+                {
+                    session = new_session()?;
+                    definitions.clear();
+                }
+                (":help", _) => writeln!(output, "{HELP}")?,
+                (":quit", _) | (":exit", _) => return Ok(session),
+                (command, _) => writeln!(errors, "Unknown command {command}, :help lists them")?
+            }
+            continue;
+        }
+
+        input.push_str(&line);
+        input.push('\n');
+        if !is_complete(&input) { continue; }
+
+        let procedures = parse_logo_code(&prepare_code(&format!("{definitions}{input}")));
+        definitions.push_str(&procedure_definitions(&input).0);
+        if let Err(error) = session.execute(procedures)
+        {
+            writeln!(errors, "Runtime error: {error}")?;
+        }
+        input.clear();
+    }
+}
@@ -0,0 +1,385 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{ self, BufRead, BufReader, Read, Write };
+use std::net::{ Ipv4Addr, TcpListener };
+use std::path::{ Path, PathBuf };
+use std::sync::mpsc::{ self, RecvTimeoutError, Sender };
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::sync::Mutex;
+use std::thread;
+use std::time::{ Duration, SystemTime };
+
+use crate::interpreter::{ execute_logo_program, InterpreterOptions };
+use crate::logo_manager::{ prepare_code, Options, WATCH_INTERVAL };
+use crate::parser::parse_logo_code;
+use crate::renderer::json::json_string;
+use crate::renderer::Renderer;
+
+pub const DEFAULT_PORT: u16 = 8080;
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);  // comments sent on idle event streams to notice closed connections
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+pub const DEFAULT_MAX_INSTRUCTIONS: u64 = 10_000_000;
+pub const MAX_CONNECTIONS: usize = 64;  // each open page keeps one for its events, further ones are turned away
+
+// Page showing a drawing, fetched again whenever the events say its program changed, errors are shown under the last good drawing
+const VIEWER: &str = r#"const drawing = document.getElementById("drawing");
+const error = document.getElementById("error");
+async function load()
+{
+    const response = await fetch("/svg/" + encodeURIComponent(name), { cache: "no-store" });
+    const text = await response.text();
+    if (response.ok)
+    {
+        drawing.innerHTML = text;
+        error.textContent = "";
+    }
+    else
+    {
+        error.textContent = text;
+    }
+}
+new EventSource("/events").onmessage = event =>
+{
+    if (JSON.parse(event.data) == name) load();
+};
+load();"#;
+
+// Clients of /events, each one is sent the names of the programs that change
+type Subscribers = Mutex<Vec<Sender<String>>>;
+
+pub struct Response
+{
+    pub status: &'static str,
+    pub content_type: &'static str,
+    pub body: Vec<u8>
+}
+impl Response
+{
+    fn new(status: &'static str, content_type: &'static str, body: impl Into<Vec<u8>>) -> Self
+    {
+        Self { status, content_type, body: body.into() }
+    }
+
+    fn not_found() -> Self
+    {
+        Response::new("404 Not Found", "text/plain; charset=utf-8", "Not found")
+    }
+}
+
+// Connections being served at once, each one holds a slot until it ends
+pub struct ConnectionLimit
+{
+    active: AtomicUsize,
+    max: usize
+}
+pub struct ConnectionSlot<'a>
+{
+    limit: &'a ConnectionLimit
+}
+impl ConnectionLimit
+{
+    pub fn new(max: usize) -> Self
+    {
+        Self { active: AtomicUsize::new(0), max }
+    }
+
+    pub fn acquire(&self) -> Option<ConnectionSlot<'_>>
+    {
+        self.active.fetch_update(Ordering::AcqRel, Ordering::Acquire, |active| (active < self.max).then_some(active + 1)).ok()?;
+        Some(ConnectionSlot { limit: self })
+    }
+}
+impl Drop for ConnectionSlot<'_>
+{
+    fn drop(&mut self)
+    {
+        self.limit.active.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+pub enum Route
+{
+    RESPONSE(Response),
+    EVENTS  // the connection stays open and receives the changes as server-sent events
+}
+
+fn html_escape(text: &str) -> String
+{
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn percent_encode(text: &str) -> String
+{
+    text.bytes().map(|byte| match byte
+    {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+        _ => format!("%{byte:02X}")
+    }).collect()
+}
+
+fn percent_decode(text: &str) -> Option<String>
+{
+    let mut bytes = vec!();
+    let mut iterator = text.bytes();
+    while let Some(byte) = iterator.next()
+    {
+        if byte == b'%'
+        {
+            let hex = [iterator.next()?, iterator.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        }
+        else
+        {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+// Names of the Logo programs in the directory with their modification times, hidden files are left out as logo_file refuses them
+fn logo_files(directory: &Path) -> BTreeMap<String, SystemTime>
+{
+    let Ok(entries) = fs::read_dir(directory) else { return BTreeMap::new(); };
+    entries.flatten()
+        .filter_map(|entry| Some((entry.file_name().into_string().ok()?, entry.metadata().ok()?)))
+        .filter(|(name, metadata)| name.ends_with(".logo") && !name.starts_with('.') && metadata.is_file())
+        .map(|(name, metadata)| (name, metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH)))
+        .collect()
+}
+
+// Program of the directory named in a URL, anything else such as a path to another directory is refused
+fn logo_file(directory: &Path, encoded_name: &str) -> Option<(String, PathBuf)>
+{
+    let name = percent_decode(encoded_name)?;
+    if name.contains(['/', '\\']) || name.starts_with('.') || !name.ends_with(".logo") { return None; }
+    let path = directory.join(&name);
+    path.is_file().then_some((name, path))
+}
+
+pub fn render_svg(logo_file_path: &Path, options: &Options) -> Result<Vec<u8>, String>
+{
+    let code = fs::read_to_string(logo_file_path).map_err(|error| format!("Unable to read {}: {error}", logo_file_path.display()))?;
+    let procedures = parse_logo_code(&prepare_code(&code));
+    let drawing = execute_logo_program(procedures, &options.interpreter).map_err(|error| format!("Runtime error: {error}"))?;
+    Ok(options.svg.render(&drawing))
+}
+
+fn index(directory: &Path) -> Response
+{
+    let links: String = logo_files(directory).keys()
+        .map(|name| format!("<li><a href=\"/view/{}\">{}</a></li>\n", percent_encode(name), html_escape(name)))
+        .collect();
+    Response::new("200 OK", "text/html; charset=utf-8", format!("<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Logo programs</title>
+</head>
+<body>
+<h1>Logo programs in {}</h1>
+<ul>
+{links}</ul>
+<script>new EventSource(\"/events\").onmessage = () => location.reload();</script>
+</body>
+</html>
+", html_escape(&directory.display().to_string())))
+}
+
+fn viewer(name: &str) -> Response
+{
+    Response::new("200 OK", "text/html; charset=utf-8", format!("<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{}</title>
+<style>
+body {{ margin: 16px; font-family: sans-serif; }}
+#error {{ color: rgb(200,0,0); }}
+</style>
+</head>
+<body>
+<p><a href=\"/\">Logo programs</a> / {}</p>
+<div id=\"drawing\"></div>
+<pre id=\"error\"></pre>
+<script>
+const name = {};
+{VIEWER}
+</script>
+</body>
+</html>
+", html_escape(name), html_escape(name), json_string(name)))
+}
+
+// Answer to a request, separate from the connection so that it can be tried without a network
+pub fn respond(method: &str, target: &str, directory: &Path, options: &Options) -> Route
+{
+    if method != "GET"
+    {
+        return Route::RESPONSE(Response::new("405 Method Not Allowed", "text/plain; charset=utf-8", "Only GET is supported"));
+    }
+    let path = target.split('?').next().unwrap_or_default();
+    if path == "/"
+    {
+        return Route::RESPONSE(index(directory));
+    }
+    if path == "/events"
+    {
+        return Route::EVENTS;
+    }
+    if let Some((name, _)) = path.strip_prefix("/view/").and_then(|name| logo_file(directory, name))
+    {
+        return Route::RESPONSE(viewer(&name));
+    }
+    if let Some((_, logo_file_path)) = path.strip_prefix("/svg/").and_then(|name| logo_file(directory, name))
+    {
+        return Route::RESPONSE(match render_svg(&logo_file_path, options)
+        {
+            Ok(svg) => Response::new("200 OK", "image/svg+xml", svg),
+            Err(error) => Response::new("500 Internal Server Error", "text/plain; charset=utf-8", error)
+        });
+    }
+    Route::RESPONSE(Response::not_found())
+}
+
+// Only pages served by this server may talk to it. Another site whose name was made to point at 127.0.0.1
+// sends its own name, which keeps its scripts from reading the programs
+pub fn allowed_host(host: &str, port: u16) -> bool
+{
+    let host = host.trim().to_ascii_lowercase();
+    host == format!("localhost:{port}") || host == format!("127.0.0.1:{port}")
+}
+
+fn write_response(stream: &mut impl Write, response: &Response) -> io::Result<()>
+{
+    write!(stream, "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+           response.status, response.content_type, response.body.len())?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+// Answer of a connection beyond MAX_CONNECTIONS, without reading its request
+pub fn refuse_connection(stream: &mut impl Write) -> io::Result<()>
+{
+    write_response(stream, &Response::new("503 Service Unavailable", "text/plain; charset=utf-8", "Too many connections"))
+}
+
+fn stream_events(stream: &mut impl Write, subscribers: &Subscribers) -> io::Result<()>
+{
+    let (sender, receiver) = mpsc::channel();
+    subscribers.lock().unwrap().push(sender);
+    stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-store\r\n\r\n")?;
+    stream.flush()?;
+    loop
+    {
+        match receiver.recv_timeout(KEEP_ALIVE_INTERVAL)
+        {
+            Ok(name) => write!(stream, "data: {}\n\n", json_string(&name))?,
+            Err(RecvTimeoutError::Timeout) => stream.write_all(b": keep alive\n\n")?,
+            Err(RecvTimeoutError::Disconnected) => return Ok(())
+        }
+        stream.flush()?;
+    }
+}
+
+// Serves a request of the server listening on the port, the Host header has to name it
+pub fn handle_connection<S: Read + Write>(stream: S, directory: &Path, port: u16, options: &Options, subscribers: &Subscribers) -> io::Result<()>
+{
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Only the host is needed of the headers, a GET has no body
+    let mut host_allowed = false;
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim().is_empty()
+    {
+        if let Some((name, value)) = header.split_once(':')
+        {
+            if name.trim().eq_ignore_ascii_case("host") { host_allowed = allowed_host(value, port); }
+        }
+        header.clear();
+    }
+    let mut words = request_line.split_whitespace();
+    let (method, target) = (words.next().unwrap_or_default(), words.next().unwrap_or_default());
+
+    let mut stream = reader.into_inner();
+    let route = if host_allowed { respond(method, target, directory, options) }
+                else { Route::RESPONSE(Response::new("421 Misdirected Request", "text/plain; charset=utf-8", "Unknown host")) };
+    match route
+    {
+        Route::RESPONSE(response) =>
+        {
+            eprintln!("{method} {target} {}", response.status);
+            write_response(&mut stream, &response)
+        }
+        Route::EVENTS =>
+        {
+            eprintln!("{method} {target}");
+            stream_events(&mut stream, subscribers)
+        }
+    }
+}
+
+// Tells the subscribers about every program added, changed or removed
+fn watch_directory(directory: &Path, subscribers: &Subscribers) -> !
+{
+    let mut modification_times = logo_files(directory);
+    loop
+    {
+        thread::sleep(WATCH_INTERVAL);
+        let current_modification_times = logo_files(directory);
+        let changed_names: Vec<&String> = current_modification_times.iter()
+            .filter(|(name, modification_time)| modification_times.get(*name) != Some(modification_time))
+            .map(|(name, _)| name)
+            .chain(modification_times.keys().filter(|name| !current_modification_times.contains_key(*name)))
+            .collect();
+        for name in changed_names
+        {
+            eprintln!("{name} changed");
+            subscribers.lock().unwrap().retain(|subscriber| subscriber.send(name.clone()).is_ok());
+        }
+        modification_times = current_modification_times;
+    }
+}
+
+// Programs are run again on every change, one that never ends must not keep its connection busy forever
+pub fn apply_default_limits(options: &mut InterpreterOptions)
+{
+    options.timeout = options.timeout.or(Some(DEFAULT_TIMEOUT));
+    options.max_instructions = options.max_instructions.or(Some(DEFAULT_MAX_INSTRUCTIONS));
+}
+
+// Serves the drawings of the programs in the directory on localhost until interrupted
+pub fn serve(directory: &Path, port: u16, mut options: Options) -> io::Result<()>
+{
+    fs::read_dir(directory)?;
+    apply_default_limits(&mut options.interpreter);
+    let options = &options;
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    let port = listener.local_addr()?.port();
+    eprintln!("Serving the Logo programs of {} at http://localhost:{port}/", directory.display());
+
+    let subscribers: &Subscribers = &Mutex::new(vec!());
+    let connection_limit = &ConnectionLimit::new(MAX_CONNECTIONS);
+    thread::scope(|scope|
+    {
+        scope.spawn(|| watch_directory(directory, subscribers));
+        for stream in listener.incoming()
+        {
+            match (stream, connection_limit.acquire())
+            {
+                // Errors only mean that the client went away
+                (Ok(stream), Some(slot)) =>
+                {
+                    scope.spawn(move ||
+                    {
+                        let _slot = slot;
+                        handle_connection(stream, directory, port, options, subscribers)
+                    });
+                }
+                (Ok(mut stream), None) => { let _ = refuse_connection(&mut stream); }
+                (Err(error), _) => eprintln!("Connection failed: {error}")
+            }
+        }
+    });
+    Ok(())
+}
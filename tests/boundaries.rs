@@ -0,0 +1,57 @@
+// What happens when the turtle reaches the edge of the canvas: window lets it go on outside, wrap brings it in
+// from the opposite edge and fence stops the program
+mod common;
+
+use logo::drawing::{ Drawing, DrawingElement };
+use logo::interpreter::RuntimeError;
+use common::run;
+
+// Ends of the lines drawn, rounded, the turtles start in the centre of the 1100 by 600 canvas
+fn segments(drawing: &Drawing) -> Vec<((f32, f32), (f32, f32))>
+{
+    drawing.get_elements().iter().filter_map(|element| match element
+    {
+        DrawingElement::SEGMENT(segment) => Some(((segment.x1.round(), segment.y1.round()), (segment.x2.round(), segment.y2.round()))),
+        _ => None
+    }).collect()
+}
+
+#[test]
+fn window_is_the_default()
+{
+    assert_eq!(segments(&run("fd 400").unwrap()), vec![((550.0, 300.0), (550.0, -100.0))]);
+    assert_eq!(segments(&run("wrap window fd 400").unwrap()), vec![((550.0, 300.0), (550.0, -100.0))]);
+}
+
+#[test]
+fn wrapped_lines_are_split_at_the_edges()
+{
+    assert_eq!(segments(&run("wrap fd 400").unwrap()), vec![((550.0, 300.0), (550.0, 0.0)), ((550.0, 600.0), (550.0, 500.0))]);
+    assert_eq!(segments(&run("wrap rt 90 fd 600 bk 100").unwrap()),
+               vec![((550.0, 300.0), (1100.0, 300.0)), ((0.0, 300.0), (50.0, 300.0)), ((50.0, 300.0), (0.0, 300.0)), ((1100.0, 300.0), (1050.0, 300.0))]);
+    // Going round the canvas more than once draws over the same column again
+    assert_eq!(segments(&run("wrap fd 1000").unwrap()).len(), 3);
+}
+
+#[test]
+fn wrapped_diagonal_lines_keep_their_direction()
+{
+    assert_eq!(segments(&run("wrap rt 45 fd 600").unwrap()), vec![((550.0, 300.0), (850.0, 0.0)), ((850.0, 600.0), (974.0, 476.0))]);
+}
+
+#[test]
+fn fence_stops_the_turtle_at_the_edges()
+{
+    assert!(matches!(run("fence fd 301"), Err(RuntimeError::TURTLE_OUT_OF_BOUNDS)));
+    assert!(matches!(run("fence rt 90 bk 551"), Err(RuntimeError::TURTLE_OUT_OF_BOUNDS)));
+    assert!(matches!(run("fence fd 100 fd 250"), Err(RuntimeError::TURTLE_OUT_OF_BOUNDS)));
+    assert_eq!(segments(&run("fence fd 300").unwrap()), vec![((550.0, 300.0), (550.0, 0.0))]);
+}
+
+#[test]
+fn wrapping_brings_turtles_outside_back_in()
+{
+    assert_eq!(segments(&run("pu fd 400 pd wrap fd 50").unwrap()), vec![((550.0, 500.0), (550.0, 450.0))]);
+    assert_eq!(segments(&run("pu rt 90 bk 2700 pd wrap fd 50").unwrap()), vec![((50.0, 300.0), (100.0, 300.0))]);
+    assert_eq!(segments(&run("pu fd 700 rt 90 fd 600 pd wrap fd 10").unwrap()), vec![((50.0, 200.0), (60.0, 200.0))]);
+}
@@ -0,0 +1,56 @@
+// Procedure calls run on a stack of frames kept on the heap, calls in tail position replace the frame of their caller
+mod common;
+
+use logo::drawing::{ ActionKind, Drawing };
+use logo::interpreter::{ InterpreterOptions, RuntimeError };
+use common::run_with;
+
+fn shallow() -> InterpreterOptions
+{
+    InterpreterOptions { max_depth: Some(10), ..Default::default() }
+}
+
+fn turns(drawing: &Drawing) -> usize
+{
+    drawing.get_actions().iter().filter(|action| matches!(action.kind, ActionKind::TURN)).count()
+}
+
+#[test]
+fn tail_recursion_runs_in_constant_depth()
+{
+    let drawing = run_with("to spin :n\nif :n < 20000 [ rt 1 spin :n + 1 ]\nend\nspin 0\n", &shallow()).unwrap();
+    assert_eq!(turns(&drawing), 20000);
+    let drawing = run_with("to spin :n\nrt 1\nif :n < 1 [ stop ]\nspin :n - 1\nend\nspin 5000\n", &shallow()).unwrap();
+    assert_eq!(turns(&drawing), 5001);
+}
+
+#[test]
+fn calls_followed_by_instructions_use_a_frame_each()
+{
+    let code = "to spin :n\nif :n < 20 [ spin :n + 1 rt 1 ]\nend\nspin 0\n";
+    assert!(matches!(run_with(code, &shallow()), Err(RuntimeError::DEPTH_LIMIT(10))));
+    let deep = InterpreterOptions { max_depth: Some(100000), ..Default::default() };
+    let drawing = run_with("to spin :n\nif :n < 50000 [ spin :n + 1 rt 1 ]\nend\nspin 0\n", &deep).unwrap();
+    assert_eq!(turns(&drawing), 50000);
+}
+
+// The caller resumes after the call, with its own variables
+#[test]
+fn procedures_return_to_their_caller()
+{
+    let code = "to square :size\nrepeat 4 [ fd :size rt 90 ]\nend\nto squares :size\nsquare :size / 2\nrt :size\nend\nsquares 40\n";
+    let drawing = run_with(code, &shallow()).unwrap();
+    let headings: Vec<f32> = drawing.get_actions().iter().filter(|action| matches!(action.kind, ActionKind::TURN)).map(|action| action.heading.round()).collect();
+    assert_eq!(headings, vec![90.0, 180.0, 270.0, 0.0, 40.0]);
+}
+
+// stop leaves the loops and blocks it is in along with its procedure, the blocks still finish what they started
+#[test]
+fn stop_leaves_the_whole_procedure()
+{
+    // repcount counts from 0, the loop stops in its fourth time round
+    let drawing = run_with("to count\nrepeat 10 [ rt 1 if 2 < repcount [ stop ] ]\nend\ncount rt 1\n", &shallow()).unwrap();
+    assert_eq!(turns(&drawing), 5);
+    let drawing = run_with("to move\nask 2 [ fd 10 stop ]\nend\nmove fd 5\n", &shallow()).unwrap();
+    assert_eq!(drawing.get_actions().last().unwrap().turtle, 1);
+}
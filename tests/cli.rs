@@ -0,0 +1,62 @@
+// Runs the command line program, whose exit status tells whether every input was rendered
+use std::fs;
+use std::path::PathBuf;
+use std::process::{ Command, Output };
+
+fn run_logo(name: &str, code: &str, arguments: &[&str]) -> Output
+{
+    let directory = std::env::temp_dir().join(format!("logo-cli-{}-{name}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let logo_file_path: PathBuf = directory.join("input.logo");
+    fs::write(&logo_file_path, code).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_logo")).arg(&logo_file_path).arg("-o").arg(directory.join("output.svg")).args(arguments).output().unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+    output
+}
+
+#[test]
+fn programs_rendered_without_errors_exit_with_success()
+{
+    assert!(run_logo("success", "fd 10", &[]).status.success());
+}
+
+#[test]
+fn runtime_errors_exit_with_failure()
+{
+    let output = run_logo("runtime_error", "setturtle 101", &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Runtime error"));
+}
+
+#[test]
+fn the_number_of_turtles_is_an_option()
+{
+    assert!(run_logo("more_turtles", "setturtle 101", &["--max-turtles", "200"]).status.success());
+    assert!(!run_logo("fewer_turtles", "setturtle 3", &["--max-turtles", "2"]).status.success());
+    assert!(!run_logo("no_turtles", "fd 10", &["--max-turtles", "0"]).status.success());
+}
+
+#[test]
+fn turtle_shapes_can_be_read_from_files()
+{
+    let shape_file_path = std::env::temp_dir().join(format!("logo-cli-{}-shape.svg", std::process::id()));
+    fs::write(&shape_file_path, "<circle r=\"4\" /><rect x=\"-1\" y=\"-12\" width=\"2\" height=\"8\" />").unwrap();
+    let shape_option = format!("pin={}", shape_file_path.display());
+    assert!(run_logo("custom_shape", "setshape \"pin", &["--shape", &shape_option]).status.success());
+    assert!(!run_logo("missing_shape", "setshape \"pin", &[]).status.success());
+    fs::write(&shape_file_path, "<path d=\"M0,0 L4,4\" />").unwrap();
+    assert!(!run_logo("invalid_shape", "fd 10", &["--shape", &shape_option]).status.success());
+    fs::remove_file(&shape_file_path).unwrap();
+    assert!(!run_logo("unreadable_shape", "fd 10", &["--shape", &shape_option]).status.success());
+}
+
+#[test]
+fn unreadable_inputs_and_unwritable_outputs_are_reported()
+{
+    let output = Command::new(env!("CARGO_BIN_EXE_logo")).arg(std::env::temp_dir().join(format!("logo-cli-{}-missing.logo", std::process::id()))).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unable to read the file"));
+    let output = run_logo("unwritable", "fd 10", &["-o", "/nonexistent/output.svg"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unable to write to file /nonexistent/output.svg"));
+}
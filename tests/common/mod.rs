@@ -0,0 +1,45 @@
+// Parses and runs programs the way logo does with its input files, shared by the integration tests.
+// Each test file only uses some of the helpers
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use logo::drawing::Drawing;
+use logo::interpreter::{ execute_logo_program, InterpreterOptions, RuntimeError };
+use logo::logo_manager::prepare_code;
+use logo::parser::{ parse_logo_code, CodeBlock };
+
+pub fn parse(code: &str) -> HashMap<String, CodeBlock>
+{
+    parse_logo_code(&prepare_code(code))
+}
+
+pub fn run_with(code: &str, options: &InterpreterOptions) -> Result<Drawing, RuntimeError>
+{
+    execute_logo_program(parse(code), options)
+}
+
+pub fn run(code: &str) -> Result<Drawing, RuntimeError>
+{
+    run_with(code, &InterpreterOptions::default())
+}
+
+// Width, height and RGB pixels of a PNG image
+#[cfg(feature = "png")]
+pub fn decode_png(png_data: &[u8]) -> (u32, u32, Vec<u8>)
+{
+    let mut reader = png::Decoder::new(png_data).read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    assert_eq!(info.color_type, png::ColorType::Rgb);
+    pixels.truncate(info.buffer_size());
+    (info.width, info.height, pixels)
+}
+
+// Colour of the pixel at x, y of an image from decode_png
+#[cfg(feature = "png")]
+pub fn pixel((width, _, pixels): &(u32, u32, Vec<u8>), x: u32, y: u32) -> [u8; 3]
+{
+    let index = ((y * width + x) * 3) as usize;
+    [pixels[index], pixels[index + 1], pixels[index + 2]]
+}
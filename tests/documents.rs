@@ -0,0 +1,92 @@
+// PDF files have to point at each of their objects from the cross-reference table by byte offset,
+// EPS files tell where on the page they draw in their bounding box comment
+mod common;
+
+use logo::renderer::Renderer;
+use logo::renderer::eps::EpsRenderer;
+use logo::renderer::pdf::PdfRenderer;
+use logo::renderer::vector::{ PageSetup, PageSize };
+use common::run;
+
+const DRAWING: &str = "ht repeat 4 [ fd 100 rt 90 ] setcolor \"red penreverse fd 50 st label \"café";
+
+fn pdf(renderer: &PdfRenderer) -> String
+{
+    // Everything outside the ASCII range is escaped, so every character is a byte
+    let pdf = String::from_utf8(renderer.render(&run(DRAWING).unwrap())).unwrap();
+    assert!(pdf.is_ascii());
+    pdf
+}
+
+fn after<'a>(text: &'a str, prefix: &str) -> &'a str
+{
+    &text[text.find(prefix).unwrap() + prefix.len()..]
+}
+
+#[test]
+fn pdf_files_start_with_the_header_and_end_with_eof()
+{
+    let pdf = pdf(&PdfRenderer::default());
+    assert!(pdf.starts_with("%PDF-1.4\n"));
+    assert!(pdf.ends_with("\n%%EOF\n"));
+}
+
+#[test]
+fn the_cross_reference_table_points_at_every_object()
+{
+    let pdf = pdf(&PdfRenderer::default());
+    let xref_offset: usize = after(&pdf, "startxref\n").lines().next().unwrap().parse().unwrap();
+    let xref = &pdf[xref_offset..];
+    assert!(xref.starts_with("xref\n0 8\n0000000000 65535 f \n"));
+    let entries: Vec<&str> = xref.lines().skip(3).take_while(|line| !line.starts_with("trailer")).collect();
+    assert_eq!(entries.len(), 7);
+    for (index, entry) in entries.iter().enumerate()
+    {
+        assert_eq!(entry.len(), 19, "{entry}");
+        assert!(entry.ends_with(" 00000 n "));
+        let offset: usize = entry[..10].parse().unwrap();
+        assert!(pdf[offset..].starts_with(&format!("{} 0 obj\n", index + 1)), "object {}", index + 1);
+    }
+    assert!(after(&pdf, "trailer\n").starts_with("<< /Size 8 /Root 1 0 R >>"));
+}
+
+#[test]
+fn the_content_stream_has_its_length()
+{
+    let pdf = pdf(&PdfRenderer::default());
+    let length: usize = after(&pdf, "<< /Length ").split(' ').next().unwrap().parse().unwrap();
+    let stream = after(&pdf, "stream\n");
+    assert!(stream[length..].starts_with("endstream"));
+    assert!(stream[..length].contains("(caf\\351) Tj"));
+    assert!(stream[..length].contains("/Reverse gs\n"));
+}
+
+#[test]
+fn the_page_has_the_paper_size()
+{
+    assert!(pdf(&PdfRenderer::default()).contains("/MediaBox [0 0 595.276 841.89]"));
+    let page = PageSetup { size: PageSize::LETTER, landscape: true, ..Default::default() };
+    assert!(pdf(&PdfRenderer { page, ..Default::default() }).contains("/MediaBox [0 0 792 612]"));
+}
+
+#[test]
+fn eps_files_have_a_bounding_box_around_the_picture()
+{
+    let eps = String::from_utf8(EpsRenderer::default().render(&run(DRAWING).unwrap())).unwrap();
+    let header: Vec<&str> = eps.lines().take(5).collect();
+    assert_eq!(header[0], "%!PS-Adobe-3.0 EPSF-3.0");
+    // The 1100 by 600 canvas is shrunk to the width of A4 inside margins of half an inch, and centred
+    assert_eq!(header[1], "%%BoundingBox: 36 278 560 564");
+    assert!(header[2].starts_with("%%HiResBoundingBox: 36 278.23"));
+    assert_eq!(header[4], "%%EndComments");
+    assert!(eps.ends_with("showpage\n%%EOF\n"));
+}
+
+#[test]
+fn fitted_eps_files_have_the_bounding_box_of_the_drawing()
+{
+    // The 21 by 121 pixels around the line are 96 pixels per inch, 0.75 points each
+    let eps = String::from_utf8(EpsRenderer { fit_to_content: true, ..Default::default() }.render(&run("ht fd 100").unwrap())).unwrap();
+    assert_eq!(eps.lines().nth(1).unwrap(), "%%BoundingBox: 289 375 306 467");
+    assert_eq!(eps.lines().nth(2).unwrap(), "%%HiResBoundingBox: 289.763 375.57 305.513 466.32");
+}
@@ -0,0 +1,88 @@
+// Programs are compiled to bytecode and run on a virtual machine, the tree-walker they used to run on is kept as the reference.
+// Both must draw the same, fail with the same error and count the same instructions
+mod common;
+
+use std::fs;
+use logo::drawing::Drawing;
+use logo::interpreter::{ walk_logo_program, InterpreterOptions, RuntimeError };
+use logo::renderer::json::action_json;
+use logo::renderer::svg::SvgRenderer;
+use logo::renderer::Renderer;
+use common::{ parse, run_with };
+
+// Everything the drawing holds, compared as text
+fn contents(result: Result<Drawing, RuntimeError>) -> String
+{
+    match result
+    {
+        Ok(drawing) =>
+        {
+            let svg = String::from_utf8(SvgRenderer{ animate: true, ..Default::default() }.render(&drawing)).unwrap();
+            let actions: Vec<String> = drawing.get_actions().iter().map(action_json).collect();
+            format!("{svg}\n{}", actions.join("\n"))
+        }
+        Err(error) => format!("error: {error}")
+    }
+}
+
+fn assert_same(code: &str, options: &InterpreterOptions)
+{
+    let walked = contents(walk_logo_program(parse(code), &InterpreterOptions{ seed: Some(1), ..options.clone() }));
+    let executed = contents(run_with(code, &InterpreterOptions{ seed: Some(1), ..options.clone() }));
+    assert!(walked == executed, "engines differ on\n{code}\nwalked: {walked}\nexecuted: {executed}");
+}
+
+#[test]
+fn resources_run_the_same()
+{
+    let mut paths: Vec<_> = fs::read_dir("resources").unwrap().chain(fs::read_dir("fuzz/regressions").unwrap()).map(|entry| entry.unwrap().path()).collect();
+    paths.sort();
+    for path in paths
+    {
+        let code = fs::read_to_string(&path).unwrap();
+        assert_same(&code, &InterpreterOptions{ max_instructions: Some(200000), ..Default::default() });
+    }
+}
+
+// Scoping of parameters and repcount, colours given by name, number or variable, and the blocks stop leaves
+#[test]
+fn variables_and_blocks_run_the_same()
+{
+    let programs = [
+        "to f :n\nfd :n\nrepeat 3 [ fd repcount rt :n repeat 2 [ rt repcount ] ]\nfd :n\nend\nf 7\n",
+        "to f :a :a\nfd :a\nend\nf 1 2\n",
+        "to f :size\nrepeat 2 [ rt repcount repeat 3 [ fd repcount ] ]\nfd :size\nend\nf 10\n",
+        "to f :c\nsetcolor :c fd 10 setcolor \"red fd 10 setcolor pick [ red 3 blue ] fd 10\nend\nf 4\nf 21\n",
+        "to f :n\nif :n < 3 [ repeat 2 [ fd 10 rt 90 f :n + 1 ] ]\nend\nf 0\n",
+        "to f\nask [ 2 3 ] [ fd 10 if who < 3 [ stop ] ]\nend\nf fd 5\n",
+        "tell [ 2 3 ] [ fd 10 ] fd 5 ask [ ] [ fd 10 ] fd 5\n",
+        "to f\nrepeat 0 [ fd 10 ]\nfd repcount\nend\nf\n",
+        "repeat 4 [ fd random 100 rt 90 ] label \"done setshape \"circle wait 3\n",
+        "to f :n\nfd :n\nend\nf\n",
+        "to f\nfd :x\nend\nf\n",
+        "fd 1 + fd 2 g 3\n",
+    ];
+    for code in programs
+    {
+        assert_same(code, &InterpreterOptions::default());
+    }
+}
+
+// The limits stop both engines at the same point
+#[test]
+fn limits_apply_the_same()
+{
+    let code = "to spin :n\nif :n < 30 [ repeat 3 [ fd 5 rt 7 ] spin :n + 1 rt 1 ]\nend\nspin 0\n";
+    for max_instructions in 0..120
+    {
+        assert_same(code, &InterpreterOptions{ max_instructions: Some(max_instructions), ..Default::default() });
+    }
+    for max_depth in 1..35
+    {
+        assert_same(code, &InterpreterOptions{ max_depth: Some(max_depth), ..Default::default() });
+    }
+    for max_primitives in 0..100
+    {
+        assert_same(code, &InterpreterOptions{ max_primitives: Some(max_primitives), ..Default::default() });
+    }
+}
@@ -0,0 +1,57 @@
+// With --fit the SVG shows the bounding box of the drawing with the margin around, instead of the whole canvas
+mod common;
+
+use logo::cli::{ parse_arguments, Command };
+use logo::renderer::Renderer;
+use logo::renderer::svg::SvgRenderer;
+use common::run;
+
+fn svg_element(code: &str, margin: f32) -> String
+{
+    let svg = String::from_utf8(SvgRenderer { fit_to_content: true, margin, ..Default::default() }.render(&run(code).unwrap())).unwrap();
+    svg.lines().next().unwrap().to_string()
+}
+
+#[test]
+fn the_view_box_surrounds_the_lines_with_the_margin()
+{
+    // Half the stroke width sticks out on every side of the line
+    assert_eq!(svg_element("ht fd 100", 0.0), "<svg width=\"1\" height=\"101\" viewBox=\"549.5 199.5 1 101\" xmlns=\"http://www.w3.org/2000/svg\">");
+    assert_eq!(svg_element("ht fd 100", 10.0), "<svg width=\"21\" height=\"121\" viewBox=\"539.5 189.5 21 121\" xmlns=\"http://www.w3.org/2000/svg\">");
+    assert_eq!(svg_element("ht rt 90 fd 100 pu fd 100 pd fd 10", 10.0), "<svg width=\"231\" height=\"21\" viewBox=\"539.5 289.5 231 21\" xmlns=\"http://www.w3.org/2000/svg\">");
+}
+
+#[test]
+fn the_view_box_includes_visible_turtles()
+{
+    // The turtle at the end of the line sticks out more than the line, only half the stroke width does at its start
+    assert_eq!(svg_element("fd 100", 10.0), "<svg width=\"50\" height=\"135.5\" viewBox=\"525 175 50 135.5\" xmlns=\"http://www.w3.org/2000/svg\">");
+}
+
+#[test]
+fn empty_drawings_show_the_whole_canvas()
+{
+    assert_eq!(svg_element("ht", 10.0), "<svg width=\"1100\" height=\"600\" xmlns=\"http://www.w3.org/2000/svg\">");
+    assert_eq!(svg_element("ht fd 100 clean", 10.0), "<svg width=\"1100\" height=\"600\" xmlns=\"http://www.w3.org/2000/svg\">");
+}
+
+#[test]
+fn the_view_box_follows_the_rotation_of_labels()
+{
+    // Labels are estimated 0.6 of their height wide per character, and written along the heading of the turtle
+    assert_eq!(svg_element("ht setlabelheight 10 rt 90 label \"abc", 0.0), "<svg width=\"18\" height=\"10\" viewBox=\"550 290 18 10\" xmlns=\"http://www.w3.org/2000/svg\">");
+    assert_eq!(svg_element("ht setlabelheight 10 label \"abc", 0.0), "<svg width=\"10\" height=\"18\" viewBox=\"540 282 10 18\" xmlns=\"http://www.w3.org/2000/svg\">");
+    assert_eq!(svg_element("ht setlabelheight 10 lt 90 label \"abc", 5.0), "<svg width=\"28\" height=\"20\" viewBox=\"527 295 28 20\" xmlns=\"http://www.w3.org/2000/svg\">");
+}
+
+#[test]
+fn fit_and_margin_are_options()
+{
+    let arguments = |arguments: &[&str]| arguments.iter().map(|argument| argument.to_string()).collect::<Vec<String>>();
+    let Ok(Command::RENDER(fitted)) = parse_arguments(&arguments(&["input.logo", "--fit", "--margin", "5"])) else { panic!("not rendering") };
+    assert!(fitted.options.svg.fit_to_content);
+    assert_eq!(fitted.options.svg.margin, 5.0);
+    let Ok(Command::RENDER(whole)) = parse_arguments(&arguments(&["input.logo", "--margin", "5"])) else { panic!("not rendering") };
+    assert!(!whole.options.svg.fit_to_content);
+    assert!(parse_arguments(&arguments(&["input.logo", "--fit", "--margin", "wide"])).is_err());
+}
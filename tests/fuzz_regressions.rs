@@ -0,0 +1,100 @@
+// Programs that made the fuzz targets of fuzz/ panic or run out of memory, each one must now end with a drawing
+// or a runtime error. The inputs found by the fuzzer are kept as they were in fuzz/regressions/,
+// the tests below are the smallest programs showing the same problems
+mod common;
+
+use std::fs;
+use std::panic;
+
+use logo::interpreter::{ InterpreterOptions, RuntimeError };
+use proptest::prelude::*;
+use common::run_with;
+
+// Same limits as the execute fuzz target
+fn limited_options() -> InterpreterOptions
+{
+    InterpreterOptions { seed: Some(1), max_instructions: Some(5000), max_primitives: Some(1000), ..Default::default() }
+}
+
+fn error(code: &str, options: &InterpreterOptions) -> RuntimeError
+{
+    match run_with(code, options)
+    {
+        Ok(_) => panic!("{code} ran without errors"),
+        Err(error) => error
+    }
+}
+
+#[test]
+fn inputs_found_by_the_fuzzer_do_not_panic()
+{
+    let mut paths: Vec<_> = fs::read_dir("fuzz/regressions").unwrap().map(|entry| entry.unwrap().path()).collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+    for path in paths
+    {
+        let code = String::from_utf8(fs::read(&path).unwrap()).unwrap();
+        let result = panic::catch_unwind(|| { let _ = run_with(&code, &limited_options()); });
+        assert!(result.is_ok(), "{} panicked", path.display());
+    }
+}
+
+#[test]
+fn malformed_expressions_are_runtime_errors()
+{
+    for code in ["fd 4-2", "fd :missing", "fd 1 +", "setcolor pick [ ]", "fd random 0", "rt random -5", "repeat 1..2 [ fd 1 ]"]
+    {
+        assert!(matches!(error(code, &limited_options()), RuntimeError::EXPRESSION(_)), "{code}");
+    }
+}
+
+#[test]
+fn procedure_cut_short_by_another_definition_is_a_runtime_error()
+{
+    let code = "to tree :size\nto bush\nfd 1\nend\ntree 5\nend\ntree 5\n";
+    assert!(matches!(error(code, &limited_options()), RuntimeError::UNKNOWN_PROCEDURE(name) if name == "tree"));
+}
+
+#[test]
+fn moving_by_an_infinite_or_unreachable_distance_is_a_runtime_error()
+{
+    assert!(matches!(error("fd 1 / 0", &limited_options()), RuntimeError::INVALID_DISTANCE(_)));
+    assert!(matches!(error("bk 0 / 0", &limited_options()), RuntimeError::INVALID_DISTANCE(_)));
+    assert!(matches!(error("wrap pu fd 1000000000000000000000000000000", &InterpreterOptions::default()), RuntimeError::INVALID_DISTANCE(_)));
+    assert!(matches!(error("wrap pu bk 22222221110", &InterpreterOptions::default()), RuntimeError::INVALID_DISTANCE(_)));
+}
+
+#[test]
+fn long_wrapped_moves_count_against_the_instruction_limit()
+{
+    assert!(matches!(error("wrap pu bk 5000000", &limited_options()), RuntimeError::INSTRUCTION_LIMIT(5000)));
+}
+
+#[test]
+fn endless_loops_stop_at_the_limits()
+{
+    assert!(matches!(error("repeat 1000000000 [ rt 1 ]", &limited_options()), RuntimeError::INSTRUCTION_LIMIT(5000)));
+    assert!(matches!(error("repeat 2000 [ fd 1 ]", &limited_options()), RuntimeError::PRIMITIVE_LIMIT(1000)));
+}
+
+#[test]
+fn waiting_past_the_last_tick_stops_the_time()
+{
+    let drawing = run_with("wait 3000000000 wait 3000000000 fd 10", &limited_options()).unwrap();
+    assert_eq!(drawing.get_duration(), u32::MAX);
+}
+
+// Words and symbols of Logo in any order, the parser and the interpreter have to turn them into a drawing or an error
+const WORDS: [&str; 40] = ["to", "end", "[", "]", "(", ")", "repeat", "if", "ifelse", "fd", "rt", "bk", ":x", "\"x", "x", "1", "-2", "0.5", "+", "*", "/",
+                           "=", "<", "make", "tell", "ask", "label", "setshape", "wait", "pu", "random", "pick", "output", "stop", "wrap", "\n", "setcolor",
+                           "to f :a", "f 1", ":"];
+
+proptest!
+{
+    #[test]
+    fn programs_of_any_words_do_not_panic(words in prop::collection::vec(prop::sample::select(&WORDS[..]), 1..16), separator in prop::sample::select(vec![" ", ""]))
+    {
+        let code = words.join(separator);
+        prop_assert!(panic::catch_unwind(|| { let _ = run_with(&code, &limited_options()); }).is_ok(), "{} panicked", code);
+    }
+}
@@ -0,0 +1,30 @@
+// GIF sizes are 16 bits, larger drawings have to be scaled down to fit
+#![cfg(feature = "gif")]
+
+mod common;
+
+use logo::renderer::Renderer;
+use logo::renderer::gif::GifRenderer;
+use common::run;
+
+fn gif_size(code: &str) -> (u16, u16)
+{
+    let drawing = run(code).unwrap();
+    let gif_data = GifRenderer { fit_to_content: true, margin: 10.0 }.render(&drawing);
+    let decoder = gif::DecodeOptions::new().read_info(gif_data.as_slice()).unwrap();
+    (decoder.width(), decoder.height())
+}
+
+#[test]
+fn drawings_fitting_a_gif_keep_their_size()
+{
+    assert_eq!(gif_size("window rt 90 fd 1000"), (1036, 50));
+}
+
+#[test]
+fn drawings_too_large_for_a_gif_are_scaled_down()
+{
+    let (width, height) = gif_size("window rt 90 fd 70000");
+    assert!(width > 60000, "{width}");
+    assert!(height < 50, "{height}");
+}
@@ -0,0 +1,159 @@
+// Renders every program of resources/ with a fixed seed and compares the SVG with the one checked in under tests/golden/,
+// numbers are allowed to differ slightly. After a change meant to alter the drawings,
+// cargo test --test golden -- --bless writes the new ones to tests/golden/ instead. svg/ is left to the example
+// drawings that logo renders without inputs, unseeded
+#![allow(clippy::upper_case_acronyms)]
+
+use std::fs;
+use std::path::{ Path, PathBuf };
+use std::process::{ Command, ExitCode, Stdio };
+
+const SEED: &str = "1";
+const GOLDEN_DIRECTORY: &str = "tests/golden";
+const TOLERANCE: f64 = 1e-3;  // relative to the larger number, and absolute below 1
+
+#[derive(Debug)]
+enum Token<'a>
+{
+    TEXT(&'a str),
+    NUMBER(f64)
+}
+
+// Splits a line into numbers and the text between them
+fn tokens(line: &str) -> Vec<Token<'_>>
+{
+    let bytes = line.as_bytes();
+    let starts_number = |index: usize|
+    {
+        let digit_at = |index: usize| bytes.get(index).is_some_and(|byte| byte.is_ascii_digit());
+        digit_at(index) || (matches!(bytes[index], b'-' | b'.') && (digit_at(index + 1) || (bytes[index] == b'-' && bytes.get(index + 1) == Some(&b'.') && digit_at(index + 2))))
+    };
+
+    let mut tokens = vec!();
+    let (mut index, mut text_start) = (0, 0);
+    while index < bytes.len()
+    {
+        if !starts_number(index)
+        {
+            index += 1;
+            continue;
+        }
+        let number_start = index;
+        index += 1;
+        while index < bytes.len() && (bytes[index].is_ascii_digit() || bytes[index] == b'.' || ((bytes[index] == b'e' || bytes[index] == b'E') && index + 1 < bytes.len() && (bytes[index + 1].is_ascii_digit() || bytes[index + 1] == b'-')))
+        {
+            index += if bytes[index] == b'e' || bytes[index] == b'E' { 2 } else { 1 };
+        }
+        if let Ok(number) = line[number_start..index].parse::<f64>()
+        {
+            if text_start < number_start { tokens.push(Token::TEXT(&line[text_start..number_start])); }
+            tokens.push(Token::NUMBER(number));
+            text_start = index;
+        }
+    }
+    if text_start < line.len() { tokens.push(Token::TEXT(&line[text_start..])); }
+    tokens
+}
+
+fn same_tokens(expected: &[Token], actual: &[Token]) -> bool
+{
+    expected.len() == actual.len() && expected.iter().zip(actual).all(|pair| match pair
+    {
+        (Token::TEXT(expected), Token::TEXT(actual)) => expected == actual,
+        (Token::NUMBER(expected), Token::NUMBER(actual)) => (expected - actual).abs() <= TOLERANCE * expected.abs().max(actual.abs()).max(1.0),
+        _ => false
+    })
+}
+
+fn shortened(line: &str) -> String
+{
+    const MAXIMUM_LENGTH: usize = 160;
+    if line.chars().count() <= MAXIMUM_LENGTH { line.to_string() } else { format!("{}...", line.chars().take(MAXIMUM_LENGTH).collect::<String>()) }
+}
+
+// First line that differs, None when the drawings match
+fn difference(expected: &str, actual: &str) -> Option<String>
+{
+    let (expected_lines, actual_lines): (Vec<&str>, Vec<&str>) = (expected.lines().collect(), actual.lines().collect());
+    for line_index in 0..expected_lines.len().max(actual_lines.len())
+    {
+        let (expected_line, actual_line) = (expected_lines.get(line_index).copied().unwrap_or(""), actual_lines.get(line_index).copied().unwrap_or(""));
+        if !same_tokens(&tokens(expected_line), &tokens(actual_line))
+        {
+            return Some(format!("line {}\n  expected: {}\n  actual:   {}", line_index + 1, shortened(expected_line), shortened(actual_line)));
+        }
+    }
+    None
+}
+
+fn render(program: &Path, output: &Path) -> Result<String, String>
+{
+    let status = Command::new(env!("CARGO_BIN_EXE_logo"))
+        .args(["--seed", SEED])
+        .arg(program)
+        .arg("-o")
+        .arg(output)
+        .stdout(Stdio::null())
+        .status()
+        .map_err(|error| format!("unable to run logo: {error}"))?;
+    if !status.success()
+    {
+        return Err(format!("logo failed with {status}"));
+    }
+    fs::read_to_string(output).map_err(|error| format!("no drawing: {error}"))
+}
+
+fn main() -> ExitCode
+{
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+    let bless = arguments.iter().any(|argument| argument == "--bless");
+    let filters: Vec<&String> = arguments.iter().filter(|argument| !argument.starts_with('-')).collect();
+
+    let mut programs: Vec<PathBuf> = fs::read_dir("resources").expect("resources/ should be readable")
+        .map(|entry| entry.expect("resources/ should be readable").path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "logo"))
+        .collect();
+    programs.sort();
+
+    let mut failures = vec!();
+    let mut count = 0;
+    for program in programs.iter()
+    {
+        let name = program.file_stem().unwrap().to_string_lossy().to_string();
+        if !filters.is_empty() && !filters.iter().any(|filter| name.contains(filter.as_str())) { continue; }
+        count += 1;
+
+        let expected_path = Path::new(GOLDEN_DIRECTORY).join(&name).with_extension("svg");
+        let output_path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(&name).with_extension("svg");
+        let result = render(program, &output_path).and_then(|actual|
+        {
+            if bless
+            {
+                return fs::write(&expected_path, actual).map_err(|error| format!("unable to write {}: {error}", expected_path.display()));
+            }
+            let expected = fs::read_to_string(&expected_path).map_err(|_| format!("{} is missing, run with --bless to create it", expected_path.display()))?;
+            match difference(&expected, &actual)
+            {
+                Some(difference) => Err(format!("{} differs at {difference}", expected_path.display())),
+                None => Ok(())
+            }
+        });
+        match result
+        {
+            Ok(()) => println!("test {name} ... {}", if bless { "blessed" } else { "ok" }),
+            Err(error) =>
+            {
+                println!("test {name} ... FAILED");
+                failures.push(format!("{name}: {error}"));
+            }
+        }
+    }
+
+    println!();
+    for failure in failures.iter()
+    {
+        println!("{failure}\n");
+    }
+    println!("golden test result: {}. {} passed; {} failed", if failures.is_empty() { "ok" } else { "FAILED" }, count - failures.len(), failures.len());
+    if failures.is_empty() { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+}
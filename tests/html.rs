@@ -0,0 +1,65 @@
+// The HTML page embeds the drawing as JSON in a script element, which nothing in the labels may end early
+mod common;
+
+use logo::drawing::{ ActionKind, Drawing, PenMode, TurtleAction };
+use logo::renderer::Renderer;
+use logo::renderer::html::HtmlRenderer;
+use serde_json::{ json, Value };
+use common::run;
+
+const SCRIPT_END: &str = "</script><script>alert(1)</script>";
+
+fn with_label(text: &str) -> Drawing
+{
+    let mut drawing = run("fd 10 rt 90").unwrap();
+    // Logo words stop at < and /, so the label is added as a program could not
+    drawing.add_action(TurtleAction { kind: ActionKind::LABEL { text: text.to_string(), font_size: 12 }, turtle: 1, tick: 0, from: (550.0, 290.0), to: (550.0, 290.0),
+                                      heading: 90.0, pen_down: true, pen_mode: PenMode::PAINT, color: "black".to_string(), width: 1.0 });
+    drawing
+}
+
+// The object assigned to drawing in the page
+fn embedded_json(page: &str) -> Value
+{
+    let start = page.find("const drawing = ").unwrap() + "const drawing = ".len();
+    let end = start + page[start..].find(";\n</script>").unwrap();
+    serde_json::from_str(&page[start..end]).unwrap()
+}
+
+#[test]
+fn labels_cannot_end_the_script()
+{
+    let page = String::from_utf8(HtmlRenderer::default().render(&with_label(SCRIPT_END))).unwrap();
+    assert_eq!(page.matches("</script>").count(), 2);
+    assert_eq!(page.matches("<script>").count(), 2);
+    assert!(page.contains("\\u003c/script>\\u003cscript>alert(1)\\u003c/script>"));
+    assert_eq!(embedded_json(&page)["actions"][2]["text"], SCRIPT_END);
+}
+
+#[test]
+fn the_page_embeds_the_drawing()
+{
+    let page = String::from_utf8(HtmlRenderer::default().render(&with_label("done"))).unwrap();
+    let drawing = embedded_json(&page);
+    assert_eq!(drawing["width"], 1100);
+    assert_eq!(drawing["height"], 600);
+    assert_eq!(drawing["viewport"], json!([0, 0, 1100, 600]));
+    assert_eq!(drawing["border"], true);
+    assert_eq!(drawing["background"], "rgb(255,255,255)");
+    assert_eq!(drawing["ticks_per_second"], 60);
+    let actions = drawing["actions"].as_array().unwrap();
+    let names: Vec<&str> = actions.iter().map(|action| action["action"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["move", "turn", "label"]);
+    assert_eq!(actions[0]["from"], json!([550, 300]));
+    assert_eq!(actions[0]["to"], json!([550, 290]));
+    assert_eq!(actions[2]["font_size"], 12);
+}
+
+#[test]
+fn fitted_pages_show_the_drawing()
+{
+    let page = String::from_utf8(HtmlRenderer { fit_to_content: true, margin: 5.0 }.render(&run("ht fd 10").unwrap())).unwrap();
+    let drawing = embedded_json(&page);
+    assert_eq!(drawing["viewport"], json!([544.5, 284.5, 11, 21]));
+    assert_eq!(drawing["border"], false);
+}
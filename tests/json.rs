@@ -0,0 +1,80 @@
+// The JSON log has the canvas and every turtle action in order, JSON Lines has the same actions one per line
+mod common;
+
+use logo::renderer::Renderer;
+use logo::renderer::json::{ JsonLinesRenderer, JsonRenderer };
+use serde_json::{ json, Value };
+use common::run;
+
+const PROGRAM: &str = "fd 10 rt 90 pu fd 5 pd setcolor \"red penreverse label \"hi wait 30 setbackground \"navy ht st tell 2 fd 20 clearscreen";
+
+const STATE_FIELDS: [&str; 10] = ["action", "turtle", "tick", "from", "to", "heading", "pen_down", "pen_mode", "color", "width"];
+
+fn log() -> Value
+{
+    serde_json::from_slice(&JsonRenderer.render(&run(PROGRAM).unwrap())).unwrap()
+}
+
+fn names(actions: &[Value]) -> Vec<&str>
+{
+    actions.iter().map(|action| action["action"].as_str().unwrap()).collect()
+}
+
+#[test]
+fn the_log_has_the_canvas_and_the_actions()
+{
+    let log = log();
+    let fields: Vec<&str> = log.as_object().unwrap().keys().map(String::as_str).collect();
+    assert_eq!(fields.len(), 6);
+    assert_eq!((&log["width"], &log["height"], &log["background"]), (&json!(1100), &json!(600), &json!("rgb(255,255,255)")));
+    assert_eq!((&log["ticks_per_second"], &log["duration"]), (&json!(60), &json!(30)));
+    assert_eq!(names(log["actions"].as_array().unwrap()),
+               vec!["move", "turn", "move", "label", "wait", "set_background", "hide", "show", "move", "clear", "home", "home"]);
+}
+
+#[test]
+fn every_action_has_the_turtle_state()
+{
+    for action in log()["actions"].as_array().unwrap()
+    {
+        let action = action.as_object().unwrap();
+        let extra = match action["action"].as_str().unwrap()
+        {
+            "label" => vec!["text", "font_size"],
+            "wait" => vec!["ticks"],
+            "set_background" => vec!["background"],
+            _ => vec![]
+        };
+        let mut expected: Vec<&str> = STATE_FIELDS.iter().copied().chain(extra).collect();
+        let mut fields: Vec<&str> = action.keys().map(String::as_str).collect();
+        expected.sort_unstable();
+        fields.sort_unstable();
+        assert_eq!(fields, expected);
+        assert!(action["from"].as_array().unwrap().len() == 2 && action["to"].as_array().unwrap().len() == 2);
+        assert!(["paint", "erase", "reverse"].contains(&action["pen_mode"].as_str().unwrap()));
+    }
+}
+
+#[test]
+fn the_actions_follow_the_turtles()
+{
+    let log = log();
+    let actions = log["actions"].as_array().unwrap();
+    assert_eq!((&actions[0]["from"], &actions[0]["to"], &actions[0]["pen_down"]), (&json!([550, 300]), &json!([550, 290]), &json!(true)));
+    assert_eq!((&actions[1]["heading"], &actions[2]["pen_down"], &actions[2]["to"]), (&json!(90), &json!(false), &json!([555, 290])));
+    assert_eq!((&actions[3]["text"], &actions[3]["color"], &actions[3]["pen_mode"]), (&json!("hi"), &json!("red"), &json!("reverse")));
+    assert_eq!((&actions[4]["ticks"], &actions[4]["tick"], &actions[5]["tick"]), (&json!(30), &json!(0), &json!(30)));
+    assert_eq!(actions[5]["background"], "navy");
+    assert_eq!((&actions[8]["turtle"], &actions[8]["from"], &actions[8]["color"]), (&json!(2), &json!([550, 300]), &json!("black")));
+    assert_eq!((&actions[10]["turtle"], &actions[10]["from"], &actions[10]["to"]), (&json!(1), &json!([555, 290]), &json!([550, 300])));
+    assert_eq!((&actions[11]["turtle"], &actions[11]["from"], &actions[11]["to"]), (&json!(2), &json!([550, 280]), &json!([550, 300])));
+}
+
+#[test]
+fn json_lines_have_one_action_each()
+{
+    let lines = String::from_utf8(JsonLinesRenderer.render(&run(PROGRAM).unwrap())).unwrap();
+    let actions: Vec<Value> = lines.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert!(lines.ends_with('\n'));
+    assert_eq!(&actions, log()["actions"].as_array().unwrap());
+}
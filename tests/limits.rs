@@ -0,0 +1,52 @@
+// Programs that never end or draw without bounds stop with a runtime error once they reach the limits of the options
+mod common;
+
+use std::time::{ Duration, Instant };
+
+use logo::interpreter::{ InterpreterOptions, RuntimeError, DEFAULT_MAX_DEPTH };
+use common::run_with;
+
+// The call is not the last instruction, each one waits for the next to come back
+const ENDLESS_RECURSION: &str = "to down :n\nif 0 < :n [ down :n - 1 fd 1 ]\nend\ndown 1000000\n";
+
+#[test]
+fn instructions_are_limited()
+{
+    let options = InterpreterOptions { max_instructions: Some(1000), ..Default::default() };
+    assert!(matches!(run_with("repeat 1000000000 [ rt 1 ]", &options), Err(RuntimeError::INSTRUCTION_LIMIT(1000))));
+    assert!(run_with("repeat 300 [ rt 1 ]", &options).is_ok());
+}
+
+#[test]
+fn call_depth_is_limited()
+{
+    let options = InterpreterOptions { max_depth: Some(20), ..Default::default() };
+    assert!(matches!(run_with(ENDLESS_RECURSION, &options), Err(RuntimeError::DEPTH_LIMIT(20))));
+    assert!(run_with("to down :n\nif 0 < :n [ down :n - 1 fd 1 ]\nend\ndown 18\n", &options).is_ok());
+}
+
+// The calls are kept on the heap, the small stack of a test thread is enough for the default limit
+#[test]
+fn endless_recursion_stops_at_the_default_depth()
+{
+    assert!(matches!(run_with(ENDLESS_RECURSION, &InterpreterOptions::default()), Err(RuntimeError::DEPTH_LIMIT(DEFAULT_MAX_DEPTH))));
+}
+
+#[test]
+fn drawn_primitives_are_limited()
+{
+    let options = InterpreterOptions { max_primitives: Some(50), ..Default::default() };
+    assert!(matches!(run_with("repeat 100 [ fd 10 rt 7 ]", &options), Err(RuntimeError::PRIMITIVE_LIMIT(50))));
+    assert!(matches!(run_with("repeat 100 [ label \"hello rt 7 ]", &options), Err(RuntimeError::PRIMITIVE_LIMIT(50))));
+    // Moves with the pen up draw nothing
+    assert!(run_with("pu repeat 100 [ fd 10 rt 7 ]", &options).is_ok());
+}
+
+#[test]
+fn time_is_limited()
+{
+    let options = InterpreterOptions { timeout: Some(Duration::from_millis(200)), ..Default::default() };
+    let start = Instant::now();
+    assert!(matches!(run_with("repeat 1000000000 [ rt 1 ]", &options), Err(RuntimeError::TIMEOUT(_))));
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
@@ -0,0 +1,70 @@
+// The pen paints with its colour, erases with the background colour or reverses what is underneath.
+// Reversing is exact in raster images, drawing the same lines twice gives back the picture there was before.
+// The turtles are hidden so that only the lines are drawn
+mod common;
+
+use logo::renderer::Renderer;
+use logo::renderer::svg::SvgRenderer;
+use common::run;
+
+fn svg(code: &str) -> String
+{
+    String::from_utf8(SvgRenderer::default().render(&run(&format!("ht {code}")).unwrap())).unwrap()
+}
+
+#[test]
+fn svg_strokes_follow_the_pen_mode()
+{
+    let painted = svg("setbackground \"navy setcolor \"red fd 10");
+    assert!(painted.contains("stroke:red;"));
+    let erased = svg("setbackground \"navy setcolor \"red pe fd 10");
+    assert!(erased.contains("stroke:navy;"));
+    assert!(!erased.contains("stroke:red;"));
+    let reversed = svg("setcolor \"red px fd 10 rt 90 fd 10 ppt fd 10");
+    assert_eq!(reversed.matches("stroke:white;mix-blend-mode:difference").count(), 2);
+    assert_eq!(reversed.matches("stroke:red;").count(), 1);
+}
+
+#[cfg(feature = "png")]
+mod png
+{
+    use logo::renderer::Renderer;
+    use logo::renderer::png::PngRenderer;
+    use super::common::{ decode_png, pixel, run };
+
+    fn pixels(code: &str) -> (u32, u32, Vec<u8>)
+    {
+        decode_png(&PngRenderer::default().render(&run(&format!("ht {code}")).unwrap()))
+    }
+
+    // Pixels that are not the same in both images
+    fn differences(first: &(u32, u32, Vec<u8>), second: &(u32, u32, Vec<u8>)) -> usize
+    {
+        assert_eq!((first.0, first.1), (second.0, second.1));
+        first.2.chunks(3).zip(second.2.chunks(3)).filter(|(first, second)| first != second).count()
+    }
+
+    #[test]
+    fn reversing_twice_restores_the_image()
+    {
+        let path = "repeat 5 [ fd 100 rt 144 ] rt 45 fd 80 bk 80 lt 45";
+        let background = pixels("setbackground \"#1e90ff");
+        assert_eq!(differences(&pixels(&format!("setbackground \"#1e90ff px {path} {path}")), &background), 0);
+        let painted = pixels("setbackground \"#1e90ff fd 50 rt 30 fd 40");
+        assert_eq!(differences(&pixels(&format!("setbackground \"#1e90ff fd 50 rt 30 fd 40 px {path} {path}")), &painted), 0);
+    }
+
+    #[test]
+    fn reversing_inverts_whole_pixels()
+    {
+        // The turtle starts in the middle of the 1100 by 600 canvas and heads up
+        let reversed = pixels("setbackground \"#1e90ff px rt 90 fd 100 rt 90 fd 100");
+        assert_eq!(pixel(&reversed, 600, 300), [0xe1, 0x6f, 0x00]);
+        assert_eq!(pixel(&reversed, 650, 300), [0xe1, 0x6f, 0x00]);
+        assert_eq!(pixel(&reversed, 600, 310), [0x1e, 0x90, 0xff]);
+        // The corner where the two lines meet is inverted once
+        assert_eq!(pixel(&reversed, 650, 300), pixel(&reversed, 650, 301));
+        assert_eq!(pixel(&reversed, 650, 350), [0xe1, 0x6f, 0x00]);
+        assert!(reversed.2.chunks(3).all(|pixel| pixel == [0x1e, 0x90, 0xff] || pixel == [0xe1, 0x6f, 0x00] || pixel == [0, 0, 0]));
+    }
+}
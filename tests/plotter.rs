@@ -0,0 +1,21 @@
+// Plotters draw the strokes in an order shortening the moves with the pen up, unless asked to keep the drawing order
+mod common;
+
+use logo::renderer::plotter::{ plot_strokes, travel_distance };
+use logo::renderer::viewport;
+use common::run;
+
+// Short lines drawn alternately on each side of the canvas
+const SCATTERED: &str = "rt 90 repeat 5 [ pu fd 400 pd fd 5 pu bk 805 pd bk 5 pu fd 405 lt 90 fd 20 rt 90 ]";
+
+#[test]
+fn reordering_shortens_the_pen_up_travel()
+{
+    let drawing = run(SCATTERED).unwrap();
+    let viewport = viewport(&drawing, false, 0.0);
+    let in_order = plot_strokes(&drawing, &viewport, 1.0, false);
+    let reordered = plot_strokes(&drawing, &viewport, 1.0, true);
+    assert_eq!(in_order.len(), 10);
+    assert_eq!(reordered.len(), 10);
+    assert!(travel_distance(&reordered) < travel_distance(&in_order) / 2.0);
+}
@@ -0,0 +1,45 @@
+// PNG images cover the canvas with its border, or the drawing with a margin around when fitting to content
+#![cfg(feature = "png")]
+
+mod common;
+
+use logo::renderer::Renderer;
+use logo::renderer::png::PngRenderer;
+use common::{ decode_png, pixel, run };
+
+// Half a pixel to the right of the centre of the canvas, the line covers whole pixels
+const RED_LINE: &str = "ht setbackground \"#1e90ff setcolor \"red pu rt 90 fd 0.5 lt 90 pd fd 100";
+
+#[test]
+fn images_show_the_background_the_border_and_the_lines()
+{
+    let image = decode_png(&PngRenderer::default().render(&run(RED_LINE).unwrap()));
+    assert_eq!((image.0, image.1), (1100, 600));
+    assert_eq!(pixel(&image, 2, 2), [0, 0, 0]);
+    assert_eq!(pixel(&image, 1097, 597), [0, 0, 0]);
+    assert_eq!(pixel(&image, 100, 100), [0x1e, 0x90, 0xff]);
+    assert_eq!(pixel(&image, 550, 250), [255, 0, 0]);
+    assert_eq!(pixel(&image, 550, 201), [255, 0, 0]);
+    assert_eq!(pixel(&image, 552, 250), [0x1e, 0x90, 0xff]);
+    assert_eq!(pixel(&image, 550, 190), [0x1e, 0x90, 0xff]);
+}
+
+#[test]
+fn the_resolution_scales_the_image()
+{
+    let image = decode_png(&PngRenderer { dpi: 192.0, ..Default::default() }.render(&run(RED_LINE).unwrap()));
+    assert_eq!((image.0, image.1), (2200, 1200));
+    assert_eq!(pixel(&image, 1101, 500), [255, 0, 0]);
+    assert_eq!(pixel(&image, 200, 200), [0x1e, 0x90, 0xff]);
+}
+
+#[test]
+fn fitting_to_content_crops_the_image()
+{
+    let image = decode_png(&PngRenderer { fit_to_content: true, margin: 10.0, ..Default::default() }.render(&run(RED_LINE).unwrap()));
+    // The drawing is as wide as the line, 1 pixel
+    assert_eq!((image.0, image.1), (21, 121));
+    assert_eq!(pixel(&image, 10, 60), [255, 0, 0]);
+    assert_eq!(pixel(&image, 0, 0), [0x1e, 0x90, 0xff]);
+    assert_eq!(pixel(&image, 10, 5), [0x1e, 0x90, 0xff]);
+}
@@ -0,0 +1,77 @@
+// The preview prints the drawing to the terminal with ANSI colours, in half blocks of two pixels or in braille dots
+mod common;
+
+use logo::renderer::Renderer;
+use logo::renderer::preview::PreviewRenderer;
+use common::run;
+
+const SQUARE: &str = "ht setcolor \"red repeat 4 [ fd 40 rt 90 ]";
+
+type Character = (char, Vec<u8>);  // with its foreground and background colours, or only the foreground
+
+// Characters of every line with the colours set before them
+fn characters(preview: &[u8]) -> Vec<Vec<Character>>
+{
+    let preview = String::from_utf8(preview.to_vec()).unwrap();
+    assert!(preview.ends_with("\x1b[0m\n"));
+    preview.lines().map(|line|
+    {
+        let mut characters = vec!();
+        let mut colors = vec!();
+        for part in line.split('\x1b').filter(|part| !part.is_empty())
+        {
+            let (code, text) = part.split_once('m').unwrap();
+            let components: Vec<u8> = code.trim_start_matches('[').split(';').skip(2).map(|component| component.parse().unwrap()).collect();
+            colors.extend(components);
+            for character in text.chars()
+            {
+                characters.push((character, std::mem::take(&mut colors)));
+            }
+        }
+        characters
+    }).collect()
+}
+
+#[test]
+fn half_blocks_show_two_pixels_each()
+{
+    // The square is 41 pixels across with its stroke, one pixel per column without a margin
+    let preview = characters(&PreviewRenderer { columns: 41, margin: 0.0, ..Default::default() }.render(&run(SQUARE).unwrap()));
+    assert_eq!(preview.len(), 21);
+    assert!(preview.iter().all(|line| line.len() == 41 && line.iter().all(|(character, _)| *character == '\u{2580}')));
+    assert_eq!(preview[0][20].1, vec![255, 0, 0, 255, 255, 255]);
+    assert_eq!(preview[10][20].1, vec![255, 255, 255, 255, 255, 255]);
+    assert_eq!(preview[10][0].1, vec![255, 0, 0, 255, 0, 0]);
+    assert_eq!(preview[20][40].1, vec![255, 0, 0, 255, 0, 0]);
+}
+
+#[test]
+fn braille_dots_are_raised_on_the_lines()
+{
+    let preview = characters(&PreviewRenderer { columns: 21, braille: true, margin: 0.0, ..Default::default() }.render(&run(SQUARE).unwrap()));
+    let text: Vec<String> = preview.iter().map(|line| line.iter().map(|(character, _)| *character).collect()).collect();
+    let (top, middle, bottom) = ("⡏⠉⠉⠉⠉⠉⠉⠉⠉⠉⠉⠉⠉⠉⠉⠉⠉⠉⠉⠉⢹", "⡇⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⢸", "⠓⠒⠒⠒⠒⠒⠒⠒⠒⠒⠒⠒⠒⠒⠒⠒⠒⠒⠒⠒⠚");
+    let expected: Vec<&str> = [vec![top], vec![middle; 9], vec![bottom]].concat();
+    assert_eq!(text, expected);
+    assert_eq!(preview[0][10].1, vec![255, 0, 0]);
+}
+
+#[test]
+fn braille_dots_stand_out_from_the_background()
+{
+    // The background is the one of setbackground, not the corner of the picture the square passes through
+    let preview = characters(&PreviewRenderer { columns: 21, braille: true, margin: 0.0, ..Default::default() }
+                                 .render(&run(&format!("setbackground \"navy {SQUARE}")).unwrap()));
+    assert_eq!(preview[5][10], ('\u{2800}', vec![0, 0, 128]));
+    assert_eq!(preview[5][0].0, '⡇');
+}
+
+#[test]
+fn the_preview_is_as_wide_as_the_columns()
+{
+    for columns in [1, 10, 80]
+    {
+        let preview = characters(&PreviewRenderer { columns, ..Default::default() }.render(&run("fd 100 rt 90 fd 50").unwrap()));
+        assert!(preview.iter().all(|line| line.len() == columns));
+    }
+}
@@ -0,0 +1,107 @@
+// The REPL runs each input once its brackets and procedure definitions are closed, keeping the turtles and procedures
+use std::fs;
+
+use logo::drawing::{ Drawing, DrawingElement };
+use logo::interpreter::Session;
+use logo::logo_manager::Options;
+use logo::repl::run_from;
+
+struct Transcript
+{
+    session: Session,
+    output: String,
+    errors: String
+}
+
+fn repl(input: &str) -> Transcript
+{
+    let (mut output, mut errors) = (vec!(), vec!());
+    let session = run_from(&Options::default(), input.as_bytes(), &mut output, &mut errors).unwrap();
+    Transcript { session, output: String::from_utf8(output).unwrap(), errors: String::from_utf8(errors).unwrap() }
+}
+
+// Lengths of the lines drawn, rounded
+fn lengths(drawing: &Drawing) -> Vec<f32>
+{
+    drawing.get_elements().iter().filter_map(|element| match element
+    {
+        DrawingElement::SEGMENT(segment) => Some(((segment.x2 - segment.x1).hypot(segment.y2 - segment.y1)).round()),
+        _ => None
+    }).collect()
+}
+
+#[test]
+fn definitions_and_brackets_continue_over_lines()
+{
+    let transcript = repl("to square :size\nrepeat 4 [\nfd :size rt 90\n]\nend\nsquare 10\n");
+    assert_eq!(lengths(&transcript.session.drawing()), vec![10.0; 4]);
+    assert_eq!(transcript.output, "Logo, :help lists the commands\n? > > > > ? ? \n");
+    assert_eq!(transcript.errors, "");
+}
+
+#[test]
+fn procedures_stay_defined_for_later_inputs()
+{
+    // The earlier inputs are not run again with the definitions they bring along
+    let transcript = repl("to step :n fd :n end step 5\nstep 10\nstep 20\n");
+    assert_eq!(lengths(&transcript.session.drawing()), vec![5.0, 10.0, 20.0]);
+}
+
+#[test]
+fn procedures_can_be_redefined()
+{
+    let transcript = repl("to shape\nfd 10\nend\nshape\nto shape\nfd 20\nend\nshape\n");
+    assert_eq!(lengths(&transcript.session.drawing()), vec![10.0, 20.0]);
+}
+
+#[test]
+fn turtles_stay_where_they_are_between_inputs()
+{
+    let drawing = repl("rt 90 fd 10\nfd 10\ntell 2\nfd 5\n").session.drawing();
+    assert_eq!(drawing.get_sprites().iter().map(|sprite| (sprite.x.round(), sprite.y.round())).collect::<Vec<(f32, f32)>>(), vec![(570.0, 300.0), (550.0, 295.0)]);
+}
+
+#[test]
+fn runtime_errors_leave_the_session_running()
+{
+    let transcript = repl("setturtle 1000\nfd 10\n");
+    assert_eq!(transcript.errors, "Runtime error: turtle 1000 does not exist, turtles are numbered from 1 to 100\n");
+    assert_eq!(lengths(&transcript.session.drawing()), vec![10.0]);
+}
+
+#[test]
+fn reset_starts_over()
+{
+    let transcript = repl("to step\nfd 10\nend\nstep rt 90\n:reset\nstep\nfd 5\n");
+    let drawing = transcript.session.drawing();
+    assert_eq!(lengths(&drawing), vec![5.0]);
+    assert_eq!(drawing.get_sprites()[0].rotation_angle.round(), 0.0);
+}
+
+#[test]
+fn save_writes_the_drawing_so_far()
+{
+    let file_path = std::env::temp_dir().join(format!("logo-repl-{}.svg", std::process::id()));
+    let transcript = repl(&format!("fd 10\n:save {}\nfd 20\n", file_path.display()));
+    let svg = fs::read_to_string(&file_path).unwrap();
+    fs::remove_file(&file_path).unwrap();
+    assert!(transcript.output.contains(&format!("Saved {}\n", file_path.display())));
+    assert!(svg.starts_with("<svg") && svg.contains("l0-10\"") && !svg.contains("-20"));
+    assert_eq!(lengths(&transcript.session.drawing()), vec![10.0, 20.0]);
+}
+
+#[test]
+fn commands_report_their_mistakes()
+{
+    let transcript = repl(":save\n:save drawing.txt\n:jump\n");
+    assert_eq!(transcript.errors, "Missing file of :save\nUnsupported output format, drawing.txt not saved\nUnknown command :jump, :help lists them\n");
+    assert!(repl(":help\n").output.contains(":reset"));
+}
+
+#[test]
+fn quit_ends_the_input()
+{
+    let transcript = repl("fd 10\n:quit\nfd 20\n");
+    assert_eq!(lengths(&transcript.session.drawing()), vec![10.0]);
+    assert!(transcript.output.ends_with("? ? "));
+}
@@ -0,0 +1,69 @@
+// clean erases the drawing and leaves the turtles where they are, clearscreen also sends them home.
+// Neither changes the background colour of setbackground
+mod common;
+
+use logo::drawing::{ Drawing, DrawingElement };
+use logo::renderer::Renderer;
+use logo::renderer::svg::SvgRenderer;
+use common::run;
+
+// Ends of the lines drawn, rounded, the turtles start in the centre of the 1100 by 600 canvas heading up
+fn segments(drawing: &Drawing) -> Vec<((f32, f32), (f32, f32))>
+{
+    drawing.get_elements().iter().filter_map(|element| match element
+    {
+        DrawingElement::SEGMENT(segment) => Some(((segment.x1.round(), segment.y1.round()), (segment.x2.round(), segment.y2.round()))),
+        _ => None
+    }).collect()
+}
+
+fn sprite_positions(drawing: &Drawing) -> Vec<(f32, f32)>
+{
+    drawing.get_sprites().iter().map(|sprite| (sprite.x.round(), sprite.y.round())).collect()
+}
+
+#[test]
+fn clean_keeps_the_turtles_where_they_are()
+{
+    let drawing = run("rt 90 fd 50 clean fd 10").unwrap();
+    assert_eq!(segments(&drawing), vec![((600.0, 300.0), (610.0, 300.0))]);
+    assert_eq!(sprite_positions(&drawing), vec![(610.0, 300.0)]);
+}
+
+#[test]
+fn clearscreen_sends_every_turtle_home()
+{
+    let drawing = run("rt 90 fd 50 tell 2 bk 30 clearscreen fd 10").unwrap();
+    assert_eq!(segments(&drawing), vec![((550.0, 300.0), (550.0, 290.0))]);
+    assert_eq!(sprite_positions(&drawing), vec![(550.0, 300.0), (550.0, 290.0)]);
+    assert_eq!(segments(&run("fd 20 cs").unwrap()), vec![]);
+}
+
+#[test]
+fn cleared_drawings_have_no_bounding_box()
+{
+    assert!(run("ht fd 100 clean").unwrap().get_bounding_box().is_none());
+    let bounding_box = run("ht fd 100 clean bk 10").unwrap().get_bounding_box().unwrap();
+    // Half the width of the line is around it
+    assert_eq!((bounding_box.min_y, bounding_box.max_y), (199.5, 210.5));
+}
+
+#[test]
+fn the_background_survives_clearing()
+{
+    for code in ["setbackground \"navy fd 10 clean", "setbg \"navy fd 10 clearscreen", "setscreencolor \"navy cs clean"]
+    {
+        assert_eq!(run(code).unwrap().background_color, "navy", "{code}");
+    }
+    assert_eq!(run("fd 10 clean").unwrap().background_color, "rgb(255,255,255)");
+}
+
+#[test]
+fn svg_drawings_are_filled_with_the_background()
+{
+    let drawing = run("setbackground \"navy ht fd 10").unwrap();
+    let svg = String::from_utf8(SvgRenderer::default().render(&drawing)).unwrap();
+    assert!(svg.contains("<rect width=\"100%\" height=\"100%\" style=\"fill:navy;stroke-width:10;stroke:rgb(0,0,0)\" />"));
+    let fitted = String::from_utf8(SvgRenderer{ fit_to_content: true, ..Default::default() }.render(&drawing)).unwrap();
+    assert!(fitted.contains("style=\"fill:navy\" />"));
+}
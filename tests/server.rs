@@ -0,0 +1,246 @@
+// Requests are answered without a network, through respond and through handle_connection on an in-memory stream
+use std::fs;
+use std::io::{ self, Read, Write };
+use std::path::{ Path, PathBuf };
+use std::sync::mpsc::Sender;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use logo::logo_manager::Options;
+use logo::server::{ allowed_host, apply_default_limits, handle_connection, refuse_connection, respond, ConnectionLimit, Response, Route };
+
+// Connection reading the request from a buffer and writing the answer to another
+struct Connection<'a>
+{
+    request: &'a [u8],
+    response: &'a Mutex<Vec<u8>>
+}
+impl Read for Connection<'_>
+{
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize>
+    {
+        self.request.read(buffer)
+    }
+}
+impl Write for Connection<'_>
+{
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize>
+    {
+        self.response.lock().unwrap().extend_from_slice(buffer);
+        Ok(buffer.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()>
+    {
+        Ok(())
+    }
+}
+
+// Directory of programs removed when the test ends
+struct Programs
+{
+    directory: PathBuf
+}
+impl Programs
+{
+    fn new(name: &str) -> Self
+    {
+        let directory = std::env::temp_dir().join(format!("logo-server-{}-{name}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("square.logo"), "repeat 4 [ fd 10 rt 90 ]").unwrap();
+        fs::write(directory.join("broken.logo"), "setturtle 0").unwrap();
+        fs::write(directory.join("endless.logo"), "to f\nf\nend\nf\n").unwrap();
+        fs::write(directory.join(".hidden.logo"), "fd 10").unwrap();
+        fs::write(directory.join("notes.txt"), "fd 10").unwrap();
+        Self { directory }
+    }
+}
+impl Drop for Programs
+{
+    fn drop(&mut self)
+    {
+        let _ = fs::remove_dir_all(&self.directory);
+    }
+}
+
+fn get(target: &str, directory: &Path, options: &Options) -> Response
+{
+    match respond("GET", target, directory, options)
+    {
+        Route::RESPONSE(response) => response,
+        Route::EVENTS => panic!("{target} is not an event stream")
+    }
+}
+
+fn body(response: &Response) -> String
+{
+    String::from_utf8_lossy(&response.body).to_string()
+}
+
+#[test]
+fn requests_are_routed_by_path()
+{
+    let programs = Programs::new("routes");
+    let options = Options::default();
+
+    let index = get("/", &programs.directory, &options);
+    assert_eq!((index.status, index.content_type), ("200 OK", "text/html; charset=utf-8"));
+    assert!(body(&index).contains("<a href=\"/view/square.logo\">square.logo</a>"));
+    assert!(!body(&index).contains("hidden") && !body(&index).contains("notes"));
+
+    let viewer = get("/view/square.logo?reload", &programs.directory, &options);
+    assert_eq!(viewer.status, "200 OK");
+    assert!(body(&viewer).contains("const name = \"square.logo\";"));
+
+    let svg = get("/svg/square.logo", &programs.directory, &options);
+    assert_eq!((svg.status, svg.content_type), ("200 OK", "image/svg+xml"));
+    assert!(body(&svg).starts_with("<svg"));
+
+    assert!(matches!(respond("GET", "/events", &programs.directory, &options), Route::EVENTS));
+    assert_eq!(get("/missing", &programs.directory, &options).status, "404 Not Found");
+    assert_eq!(get("/svg/missing.logo", &programs.directory, &options).status, "404 Not Found");
+}
+
+#[test]
+fn only_programs_of_the_directory_are_served()
+{
+    let programs = Programs::new("traversal");
+    let options = Options::default();
+    fs::write(programs.directory.parent().unwrap().join(format!("logo-server-{}-outside.logo", std::process::id())), "fd 10").unwrap();
+
+    for name in [format!("..%2Flogo-server-{}-outside.logo", std::process::id()), "..%5Csquare.logo".to_string(), "%2E%2E%2Fsquare.logo".to_string(),
+                 ".hidden.logo".to_string(), "%2Ehidden.logo".to_string(), "notes.txt".to_string(), "square.logo%".to_string()]
+    {
+        assert_eq!(get(&format!("/svg/{name}"), &programs.directory, &options).status, "404 Not Found", "{name}");
+        assert_eq!(get(&format!("/view/{name}"), &programs.directory, &options).status, "404 Not Found", "{name}");
+    }
+    fs::remove_file(programs.directory.parent().unwrap().join(format!("logo-server-{}-outside.logo", std::process::id()))).unwrap();
+}
+
+#[test]
+fn only_get_is_allowed()
+{
+    let programs = Programs::new("methods");
+    for method in ["POST", "PUT", "DELETE", "HEAD"]
+    {
+        match respond(method, "/", &programs.directory, &Options::default())
+        {
+            Route::RESPONSE(response) => assert_eq!(response.status, "405 Method Not Allowed"),
+            Route::EVENTS => panic!("{method} answered with events")
+        }
+    }
+}
+
+#[test]
+fn errors_of_the_program_are_sent_with_a_server_error()
+{
+    let programs = Programs::new("errors");
+    let mut options = Options::default();
+    let broken = get("/svg/broken.logo", &programs.directory, &options);
+    assert_eq!((broken.status, broken.content_type), ("500 Internal Server Error", "text/plain; charset=utf-8"));
+    assert_eq!(body(&broken), "Runtime error: turtle 0 does not exist, turtles are numbered from 1 to 100");
+
+    // Without limits the endless program would never answer
+    apply_default_limits(&mut options.interpreter);
+    let endless = get("/svg/endless.logo", &programs.directory, &options);
+    assert_eq!(endless.status, "500 Internal Server Error");
+    assert!(body(&endless).starts_with("Runtime error"), "{}", body(&endless));
+}
+
+#[test]
+fn limits_given_on_the_command_line_are_kept()
+{
+    let mut options = Options::default();
+    options.interpreter.timeout = Some(Duration::from_secs(1));
+    options.interpreter.max_instructions = Some(5);
+    apply_default_limits(&mut options.interpreter);
+    assert_eq!((options.interpreter.timeout, options.interpreter.max_instructions), (Some(Duration::from_secs(1)), Some(5)));
+}
+
+#[test]
+fn connections_receive_the_whole_response()
+{
+    let programs = Programs::new("connection");
+    let subscribers: Mutex<Vec<Sender<String>>> = Mutex::new(vec!());
+    let response = Mutex::new(vec!());
+    let connection = Connection { request: b"GET /svg/square.logo HTTP/1.1\r\nHost: localhost:8080\r\nAccept: */*\r\n\r\n", response: &response };
+    handle_connection(connection, &programs.directory, 8080, &Options::default(), &subscribers).unwrap();
+
+    let response = String::from_utf8(response.into_inner().unwrap()).unwrap();
+    let (head, svg) = response.split_once("\r\n\r\n").unwrap();
+    assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(head.contains("\r\nContent-Type: image/svg+xml\r\n"));
+    assert!(head.contains(&format!("\r\nContent-Length: {}\r\n", svg.len())));
+    assert!(svg.starts_with("<svg"));
+}
+
+#[test]
+fn event_streams_send_the_changed_programs()
+{
+    let programs = Programs::new("events");
+    let subscribers: Mutex<Vec<Sender<String>>> = Mutex::new(vec!());
+    let response = Mutex::new(vec!());
+    thread::scope(|scope|
+    {
+        let connection = Connection { request: b"GET /events HTTP/1.1\r\nHost: 127.0.0.1:8080\r\n\r\n", response: &response };
+        let handler = scope.spawn(|| handle_connection(connection, &programs.directory, 8080, &Options::default(), &subscribers));
+        while subscribers.lock().unwrap().is_empty()
+        {
+            thread::sleep(Duration::from_millis(10));
+        }
+        subscribers.lock().unwrap()[0].send("square.logo".to_string()).unwrap();
+        // The stream ends once the server forgets its subscriber
+        subscribers.lock().unwrap().clear();
+        handler.join().unwrap().unwrap();
+    });
+
+    let response = String::from_utf8(response.into_inner().unwrap()).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n"), "{response}");
+    assert!(response.ends_with("\r\n\r\ndata: \"square.logo\"\n\n"), "{response}");
+}
+
+#[test]
+fn requests_have_to_name_the_server_as_host()
+{
+    assert!(allowed_host(" localhost:8080\r\n", 8080));
+    assert!(allowed_host("LocalHost:8080", 8080));
+    assert!(allowed_host("127.0.0.1:8080", 8080));
+    for host in ["localhost:8081", "localhost", "127.0.0.1", "attacker.example:8080", "localhost.attacker.example:8080", "127.0.0.1:8080.example", ""]
+    {
+        assert!(!allowed_host(host, 8080), "{host}");
+    }
+
+    let programs = Programs::new("hosts");
+    let subscribers: Mutex<Vec<Sender<String>>> = Mutex::new(vec!());
+    for request in [&b"GET /svg/square.logo HTTP/1.1\r\nHost: attacker.example:8080\r\n\r\n"[..], b"GET /svg/square.logo HTTP/1.1\r\n\r\n",
+                    b"GET /events HTTP/1.1\r\nHost: localhost:9090\r\n\r\n"]
+    {
+        let response = Mutex::new(vec!());
+        handle_connection(Connection { request, response: &response }, &programs.directory, 8080, &Options::default(), &subscribers).unwrap();
+        let response = String::from_utf8(response.into_inner().unwrap()).unwrap();
+        assert!(response.starts_with("HTTP/1.1 421 Misdirected Request\r\n"), "{response}");
+        assert!(!response.contains("<svg"));
+    }
+    assert!(subscribers.lock().unwrap().is_empty());
+}
+
+#[test]
+fn connections_beyond_the_limit_are_refused()
+{
+    let limit = ConnectionLimit::new(2);
+    let first = limit.acquire().unwrap();
+    let second = limit.acquire().unwrap();
+    assert!(limit.acquire().is_none());
+    drop(first);
+    let third = limit.acquire();
+    assert!(third.is_some() && limit.acquire().is_none());
+    drop((second, third));
+    assert!(limit.acquire().is_some());
+
+    let mut response = vec!();
+    refuse_connection(&mut response).unwrap();
+    let response = String::from_utf8(response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+    assert!(response.ends_with("\r\n\r\nToo many connections"));
+}
@@ -0,0 +1,117 @@
+// Visible turtles are drawn on top of the drawing with the shape chosen by setshape, one of the built-in shapes
+// or SVG markup given in the interpreter options
+mod common;
+
+use logo::drawing::TURTLE_SHAPES;
+use logo::interpreter::{ InterpreterOptions, RuntimeError };
+use logo::renderer::{ shape_outlines, Renderer };
+use logo::renderer::svg::SvgRenderer;
+use common::{ run, run_with };
+
+const FLAG: &str = "<rect x=\"20\" y=\"-2\" width=\"6\" height=\"4\" />";
+
+fn svg(code: &str) -> String
+{
+    String::from_utf8(SvgRenderer::default().render(&run(code).unwrap())).unwrap()
+}
+
+fn with_flag() -> InterpreterOptions
+{
+    let mut options = InterpreterOptions::default();
+    options.shapes.insert("flag".to_string(), FLAG.to_string());
+    options
+}
+
+#[test]
+fn visible_turtles_are_drawn_where_they_stand()
+{
+    let svg = svg("rt 90 fd 50");
+    assert!(svg.contains(&format!("<g transform=\"translate(600,300) rotate(90)\" style=\"fill:black;fill-opacity:0.5;stroke:black;stroke-width:1\">{}</g>",
+                                  TURTLE_SHAPES["triangle"])));
+}
+
+#[test]
+fn hidden_turtles_are_not_drawn()
+{
+    assert_eq!(run("ht fd 10").unwrap().get_sprites().len(), 0);
+    assert!(!svg("hideturtle fd 10").contains("<g transform"));
+    assert_eq!(run("ht fd 10 st").unwrap().get_sprites().len(), 1);
+    assert_eq!(run("tell 2 ht tell 3").unwrap().get_sprites().len(), 2);
+    assert!(svg("ht showturtle").contains("<g transform"));
+}
+
+#[test]
+fn setshape_changes_the_shape_of_the_current_turtle()
+{
+    let drawing = run("setshape \"circle tell 2").unwrap();
+    let shapes: Vec<&str> = drawing.get_sprites().iter().map(|sprite| sprite.shape.as_str()).collect();
+    assert_eq!(shapes, vec![TURTLE_SHAPES["circle"], TURTLE_SHAPES["triangle"]]);
+    assert!(svg("setshape \"square").contains(TURTLE_SHAPES["square"]));
+}
+
+#[test]
+fn unknown_shapes_are_errors()
+{
+    let error = run("setshape \"flag").err().unwrap();
+    assert!(matches!(&error, RuntimeError::UNKNOWN_SHAPE(shape) if shape == "flag"));
+    assert_eq!(error.to_string(), "unknown turtle shape: flag");
+}
+
+#[test]
+fn shapes_can_be_added_as_svg_markup()
+{
+    let drawing = run_with("setshape \"flag fd 10 setshape \"arrow tell 2 setshape \"flag", &with_flag()).unwrap();
+    let shapes: Vec<&str> = drawing.get_sprites().iter().map(|sprite| sprite.shape.as_str()).collect();
+    assert_eq!(shapes, vec![TURTLE_SHAPES["arrow"], FLAG]);
+    let svg = String::from_utf8(SvgRenderer::default().render(&drawing)).unwrap();
+    assert!(svg.contains(&format!("\">{FLAG}</g>")));
+}
+
+#[test]
+fn the_built_in_shapes_are_valid_markup()
+{
+    for (name, markup) in TURTLE_SHAPES.iter()
+    {
+        assert!(shape_outlines(markup).is_ok(), "{name}");
+    }
+    assert_eq!(shape_outlines(FLAG).unwrap(), vec![vec![(20.0, -2.0), (26.0, -2.0), (26.0, 2.0), (20.0, 2.0)]]);
+    assert_eq!(shape_outlines("<polygon points=\"0,-10 5 5, -5,5\"/>").unwrap(), vec![vec![(0.0, -10.0), (5.0, 5.0), (-5.0, 5.0)]]);
+}
+
+#[test]
+fn shapes_only_take_geometry()
+{
+    for markup in ["", "<script>alert(1)</script>", "<circle r=\"8\" onload=\"alert(1)\" />", "<path d=\"M0,0 L1,1\" />",
+                   "<polygon points=\"0,0 1,1\" />", "<circle r=\"big\" />", "<circle r=\"8\">", "<rect width=\"4\" height />"]
+    {
+        assert!(shape_outlines(markup).is_err(), "{markup}");
+    }
+}
+
+#[cfg(feature = "png")]
+mod png
+{
+    use logo::renderer::Renderer;
+    use logo::renderer::png::PngRenderer;
+    use super::common::{ decode_png, pixel, run, run_with };
+    use super::with_flag;
+
+    const WHITE: [u8; 3] = [255, 255, 255];
+
+    #[test]
+    fn images_show_the_visible_turtles()
+    {
+        let image = decode_png(&PngRenderer::default().render(&run("setcolor \"red").unwrap()));
+        assert_ne!(pixel(&image, 550, 300), WHITE);
+        let image = decode_png(&PngRenderer::default().render(&run("setcolor \"red ht").unwrap()));
+        assert_eq!(pixel(&image, 550, 300), WHITE);
+    }
+
+    #[test]
+    fn images_show_added_shapes()
+    {
+        let image = decode_png(&PngRenderer::default().render(&run_with("setshape \"flag", &with_flag()).unwrap()));
+        assert_ne!(pixel(&image, 572, 300), WHITE);
+        assert_eq!(pixel(&image, 550, 300), WHITE);
+    }
+}
@@ -0,0 +1,63 @@
+// Connected lines of the same style are written as one path, its moves relative and rounded to the precision
+mod common;
+
+use logo::interpreter::InterpreterOptions;
+use logo::renderer::Renderer;
+use logo::renderer::svg::SvgRenderer;
+use common::{ run, run_with };
+
+fn paths(code: &str, renderer: &SvgRenderer) -> Vec<String>
+{
+    let svg = String::from_utf8(renderer.render(&run(&format!("ht {code}")).unwrap())).unwrap();
+    svg.lines().filter(|line| line.contains("<path")).map(|line| line.trim().to_string()).collect()
+}
+
+#[test]
+fn connected_lines_are_one_path()
+{
+    assert_eq!(paths("fd 10 rt 90 fd 20 rt 90 fd 10", &SvgRenderer::default()),
+               vec!["<path d=\"M550 300l0-10 20 0 0 10\" style=\"fill:none;stroke:black;stroke-width:1\" />"]);
+}
+
+#[test]
+fn style_changes_start_a_new_path()
+{
+    assert_eq!(paths("fd 10 setcolor \"red fd 10 rt 90 fd 10 setcolor \"red fd 5 penerase fd 5", &SvgRenderer::default()),
+               vec!["<path d=\"M550 300l0-10\" style=\"fill:none;stroke:black;stroke-width:1\" />",
+                    "<path d=\"M550 290l0-10 10 0 5 0\" style=\"fill:none;stroke:red;stroke-width:1\" />",
+                    "<path d=\"M565 280l5 0\" style=\"fill:none;stroke:rgb(255,255,255);stroke-width:1\" />"]);
+}
+
+#[test]
+fn gaps_and_no_merge_keep_lines_apart()
+{
+    assert_eq!(paths("fd 10 pu fd 10 pd fd 10", &SvgRenderer::default()).len(), 2);
+    assert_eq!(paths("fd 10 fd 10 fd 10", &SvgRenderer { merge_segments: false, ..Default::default() }).len(), 3);
+}
+
+#[test]
+fn coordinates_are_rounded_to_the_precision()
+{
+    let code = "rt 30 fd 10 rt 90 fd 10";
+    assert_eq!(paths(code, &SvgRenderer::default())[0], "<path d=\"M550 300l5-8.66 8.66 5\" style=\"fill:none;stroke:black;stroke-width:1\" />");
+    assert_eq!(paths(code, &SvgRenderer { precision: Some(0), ..Default::default() })[0], "<path d=\"M550 300l5-9 9 5\" style=\"fill:none;stroke:black;stroke-width:1\" />");
+    assert_eq!(paths(code, &SvgRenderer { precision: Some(1), ..Default::default() })[0], "<path d=\"M550 300l5-8.7 8.7 5\" style=\"fill:none;stroke:black;stroke-width:1\" />");
+}
+
+#[test]
+fn rounding_errors_do_not_add_up()
+{
+    // A hundred moves of 0.004 round to 0 each, yet the path ends 0.4 further as the rounded ends differ by 0.01 now and then
+    let path = &paths("pu fd 0.001 pd repeat 100 [ fd 0.004 rt 180 fd 0 rt 180 ]", &SvgRenderer::default())[0];
+    let moves: Vec<f32> = path.split('"').nth(1).unwrap().split('l').nth(1).unwrap().replace('-', " -").split_whitespace().map(|number| number.parse().unwrap()).collect();
+    let total: f32 = moves.iter().skip(1).step_by(2).sum();
+    assert!((total + 0.4).abs() < 0.005, "{total}");
+}
+
+#[test]
+fn the_fern_is_small()
+{
+    // It took 1,327,616 bytes written as separate lines with every digit
+    let drawing = run_with(include_str!("../resources/fern.logo"), &InterpreterOptions { seed: Some(1), ..Default::default() }).unwrap();
+    assert!(SvgRenderer::default().render(&drawing).len() < 132761);
+}
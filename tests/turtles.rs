@@ -0,0 +1,51 @@
+// Turtles are numbered from 1 to the maximum of the options and created the first time they are selected
+mod common;
+
+use logo::drawing::{ ActionKind, Drawing };
+use logo::interpreter::RuntimeError;
+use common::run;
+
+// Turtle and length of every move, in the order of the program
+fn moves(drawing: &Drawing) -> Vec<(usize, f32)>
+{
+    drawing.get_actions().iter().filter(|action| matches!(action.kind, ActionKind::MOVE))
+        .map(|action| (action.turtle, (action.to.0 - action.from.0).hypot(action.to.1 - action.from.1).round())).collect()
+}
+
+#[test]
+fn who_and_turtles_are_reporters()
+{
+    assert_eq!(moves(&run("tell 3 fd 10 * who fd who * 10 fd turtles + 1 fd 2 * turtles").unwrap()), vec![(3, 30.0), (3, 30.0), (3, 3.0), (3, 4.0)]);
+    assert_eq!(moves(&run("tell 2 fd :who").unwrap()), vec![(2, 2.0)]);
+    // Only whole words are reporters
+    assert_eq!(moves(&run("to whoami :turtlesleft\nfd :turtlesleft\nend\nwhoami 7").unwrap()), vec![(1, 7.0)]);
+}
+
+#[test]
+fn only_the_turtles_of_the_options_can_be_selected()
+{
+    assert!(matches!(run("setturtle 0"), Err(RuntimeError::INVALID_TURTLE(0, 100))));
+    assert!(matches!(run("setturtle 101"), Err(RuntimeError::INVALID_TURTLE(101, 100))));
+    assert!(matches!(run("setturtle -1"), Err(RuntimeError::INVALID_TURTLE(-1, 100))));
+    assert_eq!(moves(&run("setturtle 100 fd 5").unwrap()), vec![(100, 5.0)]);
+}
+
+#[test]
+fn ask_selects_back_the_previous_turtle()
+{
+    assert_eq!(moves(&run("tell 2 ask [ 3 4 ] [ fd 10 ] fd 20").unwrap()), vec![(3, 10.0), (4, 10.0), (2, 20.0)]);
+    assert_eq!(moves(&run("ask [ 3 ] [ fd 10 ] fd who").unwrap()), vec![(3, 10.0), (1, 1.0)]);
+}
+
+#[test]
+fn tell_leaves_the_last_turtle_selected()
+{
+    assert_eq!(moves(&run("tell [ 2 3 ] [ fd 10 ] fd 20").unwrap()), vec![(2, 10.0), (3, 10.0), (3, 20.0)]);
+    assert_eq!(moves(&run("tell [ 2 3 ] [ ] fd who").unwrap()), vec![(3, 3.0)]);
+}
+
+#[test]
+fn tell_without_brackets_ends_with_its_procedure()
+{
+    assert_eq!(moves(&run("to f\ntell 2\nfd 10\nend\nf\nfd 5").unwrap()), vec![(2, 10.0), (2, 5.0)]);
+}
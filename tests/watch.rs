@@ -0,0 +1,51 @@
+// --watch renders an input again when its modification time changes
+use std::fs::{ self, File };
+use std::time::{ Duration, SystemTime };
+
+use logo::logo_manager::{ changed_files, modification_times };
+
+fn touch(file_path: &str, seconds: u64)
+{
+    fs::write(file_path, "fd 10").unwrap();
+    File::options().write(true).open(file_path).unwrap().set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)).unwrap();
+}
+
+#[test]
+fn only_modified_files_have_changed()
+{
+    let directory = std::env::temp_dir().join(format!("logo-watch-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let file_paths: Vec<String> = ["first.logo", "second.logo"].iter().map(|name| directory.join(name).display().to_string()).collect();
+    let file_paths: Vec<&str> = file_paths.iter().map(String::as_str).collect();
+    touch(file_paths[0], 1000);
+    touch(file_paths[1], 1000);
+
+    let mut times = modification_times(&file_paths);
+    assert_eq!(changed_files(&file_paths, &mut times), Vec::<usize>::new());
+    touch(file_paths[1], 2000);
+    assert_eq!(changed_files(&file_paths, &mut times), vec![1]);
+    assert_eq!(changed_files(&file_paths, &mut times), Vec::<usize>::new());
+
+    // Saving may remove a file for a moment, it only counts as changed once it is back
+    fs::remove_file(file_paths[0]).unwrap();
+    assert_eq!(changed_files(&file_paths, &mut times), Vec::<usize>::new());
+    touch(file_paths[0], 1000);
+    assert_eq!(changed_files(&file_paths, &mut times), Vec::<usize>::new());
+    touch(file_paths[0], 500);
+    touch(file_paths[1], 3000);
+    assert_eq!(changed_files(&file_paths, &mut times), vec![0, 1]);
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn files_missing_from_the_start_change_when_they_appear()
+{
+    let file_path = std::env::temp_dir().join(format!("logo-watch-{}-missing.logo", std::process::id())).display().to_string();
+    let mut times = modification_times(&[&file_path]);
+    assert_eq!(times, vec![None]);
+    assert_eq!(changed_files(&[&file_path], &mut times), Vec::<usize>::new());
+    touch(&file_path, 1000);
+    assert_eq!(changed_files(&[&file_path], &mut times), vec![0]);
+    fs::remove_file(&file_path).unwrap();
+}